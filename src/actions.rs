@@ -0,0 +1,517 @@
+use bevy::input::gamepad::{Gamepad, GamepadAxis, GamepadButton};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::ConfigError;
+
+/// User bindings are saved here by the controls menu.
+pub const BINDINGS_PATH: &str = "config/bindings.ron";
+/// Analog value above which an action counts as pressed.
+const PRESS_THRESHOLD: f32 = 0.5;
+
+/// Something the player can do, independent of the device used.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Accelerate,
+    Brake,
+    /// Axis from -1 (left) to 1 (right).
+    Steer,
+    Fire,
+    /// Turret aim axis from -1 (left) to 1 (right).
+    AimHorizontal,
+    /// Turret aim axis from -1 (down) to 1 (up).
+    AimVertical,
+    EnterExit,
+    Handbrake,
+    ShiftUp,
+    ShiftDown,
+    NextWeapon,
+    PreviousWeapon,
+    Pause,
+    ToggleDebug,
+    /// Starts a shooting gallery round.
+    StartGallery,
+}
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::Accelerate,
+        Action::Brake,
+        Action::Steer,
+        Action::Fire,
+        Action::AimHorizontal,
+        Action::AimVertical,
+        Action::EnterExit,
+        Action::Handbrake,
+        Action::ShiftUp,
+        Action::ShiftDown,
+        Action::NextWeapon,
+        Action::PreviousWeapon,
+        Action::Pause,
+        Action::ToggleDebug,
+        Action::StartGallery,
+    ];
+
+    /// Axis actions range over -1..=1, all others over 0..=1.
+    pub fn is_axis(self) -> bool {
+        matches!(self, Action::Steer | Action::AimHorizontal | Action::AimVertical)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Accelerate => "Accelerate",
+            Action::Brake => "Brake",
+            Action::Steer => "Steer",
+            Action::Fire => "Fire",
+            Action::AimHorizontal => "Aim left / right",
+            Action::AimVertical => "Aim down / up",
+            Action::EnterExit => "Enter / exit vehicle",
+            Action::Handbrake => "Handbrake",
+            Action::ShiftUp => "Shift up",
+            Action::ShiftDown => "Shift down",
+            Action::NextWeapon => "Next weapon",
+            Action::PreviousWeapon => "Previous weapon",
+            Action::Pause => "Pause",
+            Action::ToggleDebug => "Debug UI",
+            Action::StartGallery => "Start gallery round",
+        }
+    }
+}
+
+/// A physical input driving an action.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Analog for triggers, 0 or 1 for other buttons.
+    GamepadButton(GamepadButton),
+    /// Two keys forming an axis, e.g. A/D for steering.
+    KeyAxis { negative: KeyCode, positive: KeyCode },
+    GamepadAxis { axis: GamepadAxis, inverted: bool },
+}
+
+impl InputBinding {
+    /// Current value of the input, in -1..=1.
+    pub fn value(
+        &self,
+        keys: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+        gamepads: &[&Gamepad],
+    ) -> f32 {
+        let key = |k: KeyCode| if keys.pressed(k) { 1.0 } else { 0.0 };
+        match *self {
+            InputBinding::Key(k) => key(k),
+            InputBinding::Mouse(b) => {
+                if mouse.pressed(b) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            InputBinding::GamepadButton(b) => strongest(gamepads.iter().map(|g| {
+                g.get(b)
+                    .unwrap_or(if g.pressed(b) { 1.0 } else { 0.0 })
+            })),
+            InputBinding::KeyAxis { negative, positive } => key(positive) - key(negative),
+            InputBinding::GamepadAxis { axis, inverted } => {
+                let v = strongest(gamepads.iter().map(|g| g.get(axis).unwrap_or(0.0)));
+                if inverted {
+                    -v
+                } else {
+                    v
+                }
+            }
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            InputBinding::Key(k) => format!("{k:?}"),
+            InputBinding::Mouse(b) => format!("Mouse {b:?}"),
+            InputBinding::GamepadButton(b) => format!("Pad {b:?}"),
+            InputBinding::KeyAxis { negative, positive } => format!("{negative:?} / {positive:?}"),
+            InputBinding::GamepadAxis { axis, inverted } => {
+                format!("Pad {axis:?}{}", if *inverted { " (inverted)" } else { "" })
+            }
+        }
+    }
+}
+
+fn strongest(values: impl Iterator<Item = f32>) -> f32 {
+    values.fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a })
+}
+
+/// Inputs bound to each action. Several bindings per action are allowed,
+/// the one with the largest value wins.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(transparent)]
+pub struct ActionBindings {
+    pub bindings: BTreeMap<Action, Vec<InputBinding>>,
+}
+
+impl Default for ActionBindings {
+    fn default() -> Self {
+        use InputBinding as B;
+        let bindings = BTreeMap::from([
+            (
+                Action::Accelerate,
+                vec![
+                    B::Key(KeyCode::KeyW),
+                    B::Key(KeyCode::ArrowUp),
+                    B::GamepadButton(GamepadButton::RightTrigger2),
+                ],
+            ),
+            (
+                Action::Brake,
+                vec![
+                    B::Key(KeyCode::KeyS),
+                    B::Key(KeyCode::ArrowDown),
+                    B::GamepadButton(GamepadButton::LeftTrigger2),
+                ],
+            ),
+            (
+                Action::Steer,
+                vec![
+                    B::KeyAxis {
+                        negative: KeyCode::KeyA,
+                        positive: KeyCode::KeyD,
+                    },
+                    B::KeyAxis {
+                        negative: KeyCode::ArrowLeft,
+                        positive: KeyCode::ArrowRight,
+                    },
+                    B::GamepadAxis {
+                        axis: GamepadAxis::LeftStickX,
+                        inverted: false,
+                    },
+                ],
+            ),
+            (
+                Action::Fire,
+                vec![
+                    B::Key(KeyCode::Space),
+                    B::Mouse(MouseButton::Left),
+                    B::GamepadButton(GamepadButton::South),
+                ],
+            ),
+            (
+                Action::AimHorizontal,
+                vec![
+                    B::KeyAxis {
+                        negative: KeyCode::KeyJ,
+                        positive: KeyCode::KeyL,
+                    },
+                    B::GamepadAxis {
+                        axis: GamepadAxis::RightStickX,
+                        inverted: false,
+                    },
+                ],
+            ),
+            (
+                Action::AimVertical,
+                vec![
+                    B::KeyAxis {
+                        negative: KeyCode::KeyK,
+                        positive: KeyCode::KeyI,
+                    },
+                    B::GamepadAxis {
+                        axis: GamepadAxis::RightStickY,
+                        inverted: false,
+                    },
+                ],
+            ),
+            (
+                Action::EnterExit,
+                vec![B::Key(KeyCode::KeyE), B::GamepadButton(GamepadButton::North)],
+            ),
+            (
+                Action::Handbrake,
+                vec![B::Key(KeyCode::KeyQ), B::GamepadButton(GamepadButton::West)],
+            ),
+            (
+                Action::ShiftUp,
+                vec![B::Key(KeyCode::KeyR), B::GamepadButton(GamepadButton::RightTrigger)],
+            ),
+            (
+                Action::ShiftDown,
+                vec![B::Key(KeyCode::KeyF), B::GamepadButton(GamepadButton::LeftTrigger)],
+            ),
+            (
+                Action::NextWeapon,
+                vec![B::Key(KeyCode::KeyX), B::GamepadButton(GamepadButton::DPadRight)],
+            ),
+            (
+                Action::PreviousWeapon,
+                vec![B::Key(KeyCode::KeyZ), B::GamepadButton(GamepadButton::DPadLeft)],
+            ),
+            (
+                Action::Pause,
+                vec![B::Key(KeyCode::Escape), B::GamepadButton(GamepadButton::Start)],
+            ),
+            (Action::ToggleDebug, vec![B::Key(KeyCode::F1)]),
+            (
+                Action::StartGallery,
+                vec![B::Key(KeyCode::KeyG), B::GamepadButton(GamepadButton::Select)],
+            ),
+        ]);
+        Self { bindings }
+    }
+}
+
+impl ActionBindings {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn get(&self, action: Action) -> &[InputBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn add(&mut self, action: Action, binding: InputBinding) {
+        let list = self.bindings.entry(action).or_default();
+        if !list.contains(&binding) {
+            list.push(binding);
+        }
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.bindings.remove(&action);
+    }
+
+    /// Value of `action`, clamped to its range.
+    pub fn value(
+        &self,
+        action: Action,
+        keys: &ButtonInput<KeyCode>,
+        mouse: &ButtonInput<MouseButton>,
+        gamepads: &[&Gamepad],
+    ) -> f32 {
+        let v = strongest(self.get(action).iter().map(|b| b.value(keys, mouse, gamepads)));
+        if action.is_axis() {
+            v.clamp(-1.0, 1.0)
+        } else {
+            v.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Where the user's [`ActionBindings`] are loaded from and saved to. `None`
+/// keeps them in memory only, e.g. when headless. Insert it before
+/// [`ActionsPlugin`] to change it.
+#[derive(Resource, Clone, Debug)]
+pub struct BindingsFile(pub Option<PathBuf>);
+
+impl Default for BindingsFile {
+    fn default() -> Self {
+        Self(Some(PathBuf::from(BINDINGS_PATH)))
+    }
+}
+
+impl BindingsFile {
+    /// Loads the bindings in the file, or the defaults if there is none.
+    pub fn load(&self) -> ActionBindings {
+        let Some(path) = &self.0 else { return ActionBindings::default(); };
+        match ActionBindings::load(path) {
+            Ok(bindings) => bindings,
+            Err(ConfigError::Io(_)) => ActionBindings::default(),
+            Err(e) => {
+                error!("Failed to load {}: {e}", path.display());
+                ActionBindings::default()
+            }
+        }
+    }
+
+    pub fn save(&self, bindings: &ActionBindings) -> Result<(), ConfigError> {
+        match &self.0 {
+            Some(path) => bindings.save(path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Values of all actions for the current frame. Gameplay systems read this
+/// instead of device input.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    values: HashMap<Action, f32>,
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn value(&self, action: Action) -> f32 {
+        self.values.get(&action).copied().unwrap_or(0.0)
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// True on the frame the action was pressed. Only reliable in `Update`,
+    /// `FixedUpdate` can run zero or several times per frame.
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Sets an action value directly, e.g. from tests or replays.
+    pub fn set(&mut self, action: Action, value: f32) {
+        let was_pressed = self.pressed(action);
+        let is_pressed = value.abs() >= PRESS_THRESHOLD;
+        self.values.insert(action, value);
+        if is_pressed {
+            self.pressed.insert(action);
+            if !was_pressed {
+                self.just_pressed.insert(action);
+            }
+        } else {
+            self.pressed.remove(&action);
+        }
+    }
+}
+
+/// True while the pointer is over a UI window, set by the debug UI. Mouse
+/// bindings and mouse aiming are ignored meanwhile, so clicking a button
+/// doesn't also fire.
+#[derive(Resource, Default, Debug)]
+pub struct PointerOverUi(pub bool);
+
+/// Waits for the next input to bind to `action` in the controls menu.
+#[derive(Resource, Default)]
+pub struct BindingCapture {
+    pub action: Option<Action>,
+    /// First key of a key axis, waiting for the second.
+    pending_key: Option<KeyCode>,
+}
+
+impl BindingCapture {
+    pub fn start(&mut self, action: Action) {
+        self.action = Some(action);
+        self.pending_key = None;
+    }
+
+    pub fn cancel(&mut self) {
+        self.action = None;
+        self.pending_key = None;
+    }
+
+    /// True while waiting for the positive key of a key axis.
+    pub fn waiting_for_second_key(&self) -> bool {
+        self.pending_key.is_some()
+    }
+}
+
+/// Maps keyboard, mouse and gamepad input to [`ActionState`] and loads the
+/// user's [`ActionBindings`] from the [`BindingsFile`].
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        let file = app.world().get_resource::<BindingsFile>().cloned().unwrap_or_default();
+        app.insert_resource(file.load())
+            .insert_resource(file)
+            .init_resource::<ActionState>()
+            .init_resource::<BindingCapture>()
+            .init_resource::<PointerOverUi>()
+            .add_systems(
+                PreUpdate,
+                (capture_binding, update_action_state)
+                    .chain()
+                    .after(InputSystem),
+            );
+    }
+}
+
+fn update_action_state(
+    bindings: Res<ActionBindings>,
+    capture: Res<BindingCapture>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    pointer_over_ui: Res<PointerOverUi>,
+    gamepads: Query<&Gamepad>,
+    mut state: ResMut<ActionState>,
+) {
+    let no_mouse = ButtonInput::default();
+    let mouse = if pointer_over_ui.0 { &no_mouse } else { &*mouse };
+    let gamepads: Vec<&Gamepad> = gamepads.iter().collect();
+    state.just_pressed.clear();
+    for action in Action::ALL {
+        state.set(action, bindings.value(action, &keys, mouse, &gamepads));
+    }
+    // Presses go to the controls menu while it is listening.
+    if capture.action.is_some() || (capture.is_changed() && !capture.is_added()) {
+        state.just_pressed.clear();
+    }
+}
+
+fn capture_binding(
+    mut capture: ResMut<BindingCapture>,
+    mut bindings: ResMut<ActionBindings>,
+    file: Res<BindingsFile>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let Some(action) = capture.action else { return; };
+    if keys.just_pressed(KeyCode::Escape) {
+        capture.cancel();
+        return;
+    }
+
+    let binding = if let Some(&key) = keys.get_just_pressed().next() {
+        match (action.is_axis(), capture.pending_key) {
+            (false, _) => Some(InputBinding::Key(key)),
+            (true, None) => {
+                capture.pending_key = Some(key);
+                None
+            }
+            (true, Some(negative)) => Some(InputBinding::KeyAxis {
+                negative,
+                positive: key,
+            }),
+        }
+    } else if let Some(&button) = mouse.get_just_pressed().next() {
+        (!action.is_axis()).then_some(InputBinding::Mouse(button))
+    } else {
+        gamepads.iter().find_map(|pad| {
+            if action.is_axis() {
+                pad.get_analog_axes().find_map(|input| {
+                    let bevy::input::gamepad::GamepadInput::Axis(axis) = *input else {
+                        return None;
+                    };
+                    let v = pad.get(axis)?;
+                    (v.abs() > PRESS_THRESHOLD).then_some(InputBinding::GamepadAxis {
+                        axis,
+                        inverted: v < 0.0,
+                    })
+                })
+            } else {
+                pad.get_just_pressed()
+                    .next()
+                    .map(|&button| InputBinding::GamepadButton(button))
+            }
+        })
+    };
+
+    if let Some(binding) = binding {
+        bindings.add(action, binding);
+        capture.cancel();
+        if let Err(e) = file.save(&bindings) {
+            error!("Failed to save bindings: {e}");
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::log::tracing_subscriber::{self, EnvFilter};
+use bevy::log::info;
+use game_demo::relay::RelayServer;
+
+const DEFAULT_ADDR: &str = "0.0.0.0:8080";
+
+/// Local stand-in for the AWS socket backend.
+///
+/// Usage: `cargo run --bin relay_server -- [--addr 0.0.0.0:8080]`
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .init();
+
+    let mut addr = DEFAULT_ADDR.to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => {
+                addr = args.next().unwrap_or_else(|| {
+                    eprintln!("--addr expects a value");
+                    std::process::exit(2);
+                });
+            }
+            other => {
+                eprintln!("Unknown argument: {other}");
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let server = RelayServer::bind(&addr).await?;
+    info!("Relay listening on ws://{}", server.local_addr()?);
+    server.run().await
+}
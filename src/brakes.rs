@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_state::GameState;
+use crate::vehicle::Vehicle;
+use crate::vehicle_systems::{compute_tire_forces, RaycastWheel};
+
+/// Slip ratio beyond which ABS releases a wheel's brake.
+const ABS_SLIP: f32 = 0.15;
+
+/// Brake setup of a vehicle. Missing fields take their default when loaded
+/// from a vehicle definition.
+#[derive(Component, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Brakes {
+    /// Total foot brake torque over all wheels (N·m).
+    pub max_torque: f32,
+    /// Share of the foot brake on the front axle.
+    pub front_bias: f32,
+    /// Releases wheels that start to lock under the foot brake.
+    pub abs: bool,
+    /// Handbrake torque on each rear wheel (N·m). Not affected by ABS.
+    pub handbrake_torque: f32,
+}
+
+impl Default for Brakes {
+    fn default() -> Self {
+        Self {
+            max_torque: 5000.0,
+            front_bias: 0.65,
+            abs: true,
+            handbrake_torque: 2000.0,
+        }
+    }
+}
+
+impl Brakes {
+    /// Brake torque on one wheel of a two-wheel axle for the `brake` and
+    /// `handbrake` inputs (0..=1), given the wheel's last slip ratio. A
+    /// locking wheel slips negatively going forwards and positively in
+    /// reverse.
+    pub fn wheel_torque(&self, brake: f32, handbrake: f32, is_front: bool, slip_ratio: f32) -> f32 {
+        let bias = if is_front { self.front_bias } else { 1.0 - self.front_bias };
+        let mut foot = brake * self.max_torque * bias * 0.5;
+        if self.abs && slip_ratio.abs() > ABS_SLIP {
+            foot = 0.0;
+        }
+        let hand = if is_front { 0.0 } else { handbrake * self.handbrake_torque };
+        foot + hand
+    }
+}
+
+/// Plugin applying vehicle brakes.
+pub struct BrakesPlugin;
+
+impl Plugin for BrakesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            apply_brakes
+                .before(compute_tire_forces)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Sets the brake torque of each wheel from its vehicle's brake inputs.
+pub fn apply_brakes(
+    vehicles: Query<(&Vehicle, &Brakes, &Children)>,
+    mut wheels: Query<&mut RaycastWheel>,
+) {
+    for (vehicle, brakes, children) in &vehicles {
+        for child in children.iter() {
+            let Ok(mut wheel) = wheels.get_mut(child) else { continue; };
+            wheel.brake_torque =
+                brakes.wheel_torque(vehicle.brake, vehicle.handbrake, wheel.is_front, wheel.slip_ratio);
+        }
+    }
+}
@@ -2,7 +2,8 @@ use bevy::prelude::*;
 use bevy::log::info;
 use bevy_egui::{egui, EguiContexts};
 
-use crate::socket_client::SocketClient;
+use crate::protocol::{NetMessage, ANONYMOUS_CLIENT};
+use crate::socket_client::{ConnectionStatus, NetMessageReceived, SocketClient, SocketDisconnected, SocketError};
 
 #[derive(Resource, Default)]
 pub struct ChatLog {
@@ -15,21 +16,59 @@ pub struct ChatPlugin;
 impl Plugin for ChatPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ChatLog>()
-            .add_systems(Update, receive_messages)
+            .add_systems(Update, (receive_messages, log_connection_events))
             .add_systems(bevy_egui::EguiContextPass, chat_ui);
     }
 }
 
-fn receive_messages(mut client: ResMut<SocketClient>, mut log: ResMut<ChatLog>) {
-    while let Some(msg) = client.try_recv() {
-        info!("Received message: {}", msg);
-        log.messages.push(msg);
+fn receive_messages(mut events: EventReader<NetMessageReceived>, mut log: ResMut<ChatLog>) {
+    for ev in events.read() {
+        let who = if ev.sender == ANONYMOUS_CLIENT {
+            "anon".to_string()
+        } else {
+            format!("{:04x}", ev.sender & 0xffff)
+        };
+        match &ev.message {
+            NetMessage::Chat { text } => {
+                info!("Received message: {}", text);
+                log.messages.push(format!("{who}: {text}"));
+            }
+            NetMessage::Join { name } => log.messages.push(format!("* {name} joined")),
+            NetMessage::Leave => log.messages.push(format!("* {who} left")),
+            _ => {}
+        }
+    }
+}
+
+fn log_connection_events(
+    mut disconnected: EventReader<SocketDisconnected>,
+    mut errors: EventReader<SocketError>,
+    mut log: ResMut<ChatLog>,
+) {
+    for ev in disconnected.read() {
+        match &ev.reason {
+            Some(reason) => log.messages.push(format!("* disconnected: {reason}")),
+            None => log.messages.push("* disconnected".to_string()),
+        }
+    }
+    // Repeated failures while reconnecting would flood the log, keep the last.
+    if let Some(ev) = errors.read().last() {
+        log.messages.push(format!("* connection error: {}", ev.message));
     }
 }
 
 fn chat_ui(mut ctxs: EguiContexts, mut log: ResMut<ChatLog>, client: Res<SocketClient>) {
     let ctx = ctxs.ctx_mut();
     egui::Window::new("Chat").show(ctx, |ui| {
+        let status = match client.status() {
+            ConnectionStatus::Idle => "offline".to_string(),
+            ConnectionStatus::Connecting => "connecting...".to_string(),
+            ConnectionStatus::Connected => "connected".to_string(),
+            ConnectionStatus::Reconnecting { attempt, delay } => {
+                format!("reconnecting (attempt {attempt}, retrying in {:.1}s)", delay.as_secs_f32())
+            }
+        };
+        ui.label(format!("Status: {status}"));
         egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
             for msg in &log.messages {
                 ui.label(msg);
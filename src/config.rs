@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::globals::GameParams;
+use crate::vehicle_systems::SuspensionTuning;
+
+/// Config file read at startup unless `--config` points elsewhere.
+pub const DEFAULT_CONFIG_PATH: &str = "config/game.ron";
+/// Directory holding named presets saved from the debug UI.
+pub const PRESET_DIR: &str = "config/presets";
+/// How often the config file is checked for changes (seconds).
+const RELOAD_INTERVAL: f32 = 1.0;
+
+/// Everything that can be tuned from the config file.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct GameConfig {
+    pub params: GameParams,
+    pub suspension: SuspensionTuning,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(String),
+    UnknownArgument(String),
+    MissingValue(String),
+    InvalidValue { key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{e}"),
+            ConfigError::Parse(e) => write!(f, "invalid config: {e}"),
+            ConfigError::UnknownArgument(arg) => write!(f, "unknown argument {arg}"),
+            ConfigError::MissingValue(arg) => write!(f, "{arg} expects a value"),
+            ConfigError::InvalidValue { key, message } => {
+                write!(f, "invalid value for {key}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl GameConfig {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path)?;
+        ron::from_str(&text).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| ConfigError::Parse(e.to_string()))?;
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Sets a single field by name, e.g. `max_speed` or `socket_url`.
+    /// `GameParams` fields are searched first, then `SuspensionTuning`.
+    /// Values are parsed as JSON, falling back to a plain string.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = |message: String| ConfigError::InvalidValue {
+            key: key.to_string(),
+            message,
+        };
+        let mut root = serde_json::to_value(&*self).map_err(|e| invalid(e.to_string()))?;
+        let parsed = serde_json::from_str::<Value>(value)
+            .unwrap_or_else(|_| Value::String(value.to_string()));
+
+        let section = ["params", "suspension"]
+            .into_iter()
+            .find(|s| root[*s].get(key).is_some())
+            .ok_or_else(|| ConfigError::UnknownArgument(format!("--{}", key.replace('_', "-"))))?;
+        root[section][key] = parsed;
+
+        *self = serde_json::from_value(root).map_err(|e| invalid(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Command line options understood by the game binary.
+#[derive(Clone, Debug, Default)]
+pub struct CliArgs {
+    pub config_path: Option<PathBuf>,
+    /// Run without a window, see [`HeadlessPlugin`](crate::headless::HeadlessPlugin).
+    pub headless: bool,
+    /// `(field, value)` pairs from `--field-name value` flags.
+    pub overrides: Vec<(String, String)>,
+}
+
+impl CliArgs {
+    /// Parses `--config <path>`, `--headless` and `--<field-name> <value>` pairs.
+    /// Dashes in field names map to underscores, so `--max-speed 60` sets
+    /// `GameParams::max_speed`. Arguments it can't make sense of are skipped
+    /// and returned as errors, leaving the rest in effect.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> (Self, Vec<ConfigError>) {
+        let mut cli = CliArgs::default();
+        let mut errors = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                errors.push(ConfigError::UnknownArgument(arg));
+                continue;
+            };
+            if flag == "headless" {
+                cli.headless = true;
+                continue;
+            }
+            let (name, inline) = match flag.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (flag.to_string(), None),
+            };
+            let Some(value) = inline.or_else(|| args.next()) else {
+                errors.push(ConfigError::MissingValue(arg));
+                continue;
+            };
+            if name == "config" {
+                cli.config_path = Some(PathBuf::from(value));
+            } else {
+                cli.overrides.push((name.replace('-', "_"), value));
+            }
+        }
+        (cli, errors)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.config_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+    }
+
+    /// Applies every override to `config`. Bad ones are skipped and
+    /// returned, the others still apply.
+    pub fn apply(&self, config: &mut GameConfig) -> Result<(), Vec<ConfigError>> {
+        let errors: Vec<ConfigError> = self
+            .overrides
+            .iter()
+            .filter_map(|(key, value)| config.set(key, value).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Where the active config came from, used for hot reloading.
+#[derive(Resource)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub cli: CliArgs,
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl ConfigSource {
+    /// Writes the live values back to the config file.
+    pub fn save(&mut self, config: &GameConfig) -> Result<(), ConfigError> {
+        config.save(&self.path)?;
+        self.modified = modified_time(&self.path);
+        Ok(())
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Loads the config file (or defaults when missing), applies command line
+/// overrides and inserts [`GameParams`] and [`SuspensionTuning`].
+/// Add it before any plugin that reads those resources; it can go before
+/// `DefaultPlugins` or [`HeadlessPlugin`](crate::headless::HeadlessPlugin).
+#[derive(Default)]
+pub struct ConfigPlugin {
+    /// Command line arguments, without the program name.
+    pub args: Vec<String>,
+}
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        let mut log = ConfigLog::default();
+        let (cli, errors) = CliArgs::parse(self.args.iter().cloned());
+        log.errors.extend(errors.iter().map(ToString::to_string));
+        let path = cli.config_path();
+        let config = load_config(&path, &cli, &mut log);
+
+        app.insert_resource(config.params)
+            .insert_resource(config.suspension)
+            .insert_resource(ConfigSource {
+                modified: modified_time(&path),
+                path,
+                cli,
+                timer: Timer::from_seconds(RELOAD_INTERVAL, TimerMode::Repeating),
+            })
+            .add_systems(Startup, move || log.report())
+            .add_systems(Update, hot_reload_config);
+    }
+}
+
+/// What loading the config found. Reported from `Startup` rather than
+/// logged right away, since the plugin may be built before logging is set
+/// up.
+#[derive(Default)]
+struct ConfigLog {
+    info: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl ConfigLog {
+    fn report(&self) {
+        for message in &self.info {
+            info!("{message}");
+        }
+        for message in &self.errors {
+            error!("{message}");
+        }
+    }
+}
+
+fn load_config(path: &Path, cli: &CliArgs, log: &mut ConfigLog) -> GameConfig {
+    let mut config = match GameConfig::load(path) {
+        Ok(config) => {
+            log.info.push(format!("Loaded config from {}", path.display()));
+            config
+        }
+        Err(ConfigError::Io(_)) => {
+            log.info.push(format!("No config at {}, using defaults", path.display()));
+            GameConfig::default()
+        }
+        Err(e) => {
+            log.errors.push(format!("Failed to load {}: {e}", path.display()));
+            GameConfig::default()
+        }
+    };
+    if let Err(errors) = cli.apply(&mut config) {
+        log.errors.extend(errors.iter().map(ToString::to_string));
+    }
+    config
+}
+
+fn hot_reload_config(
+    time: Res<Time>,
+    mut source: ResMut<ConfigSource>,
+    mut params: ResMut<GameParams>,
+    mut tuning: ResMut<SuspensionTuning>,
+) {
+    if !source.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified = modified_time(&source.path);
+    if modified.is_none() || modified == source.modified {
+        return;
+    }
+    source.modified = modified;
+
+    match GameConfig::load(&source.path) {
+        Ok(mut config) => {
+            // Command line flags keep priority over the file.
+            if let Err(errors) = source.cli.apply(&mut config) {
+                for e in errors {
+                    error!("{e}");
+                }
+            }
+            *params = config.params;
+            *tuning = config.suspension;
+            info!("Reloaded config from {}", source.path.display());
+        }
+        Err(e) => error!("Failed to reload {}: {e}", source.path.display()),
+    }
+}
+
+fn preset_path(name: &str) -> PathBuf {
+    let safe: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    Path::new(PRESET_DIR).join(format!("{safe}.ron"))
+}
+
+/// Saves `config` as a named preset.
+pub fn save_preset(name: &str, config: &GameConfig) -> Result<(), ConfigError> {
+    config.save(&preset_path(name))
+}
+
+pub fn load_preset(name: &str) -> Result<GameConfig, ConfigError> {
+    GameConfig::load(&preset_path(name))
+}
+
+/// Names of all saved presets, sorted.
+pub fn list_presets() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(PRESET_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            (path.extension()? == "ron")
+                .then(|| path.file_stem()?.to_str().map(str::to_string))
+                .flatten()
+        })
+        .collect();
+    names.sort();
+    names
+}
@@ -1,9 +1,20 @@
 use bevy::prelude::*;
-use bevy::input::{keyboard::KeyCode, ButtonInput};
 use bevy_egui::{egui, EguiContextPass, EguiContexts, EguiPlugin};
 
-use crate::globals::GameParams;
-use crate::input::Player;
+use crate::actions::{Action, ActionState, PointerOverUi};
+use crate::brakes::Brakes;
+use crate::config::{self, ConfigSource, GameConfig};
+use crate::drivetrain::{Differential, DriveLayout, Drivetrain, Gearbox};
+use crate::globals::{Controlled, GameParams};
+use crate::health::Health;
+use crate::input::{respawn_player, Player};
+use crate::level::{LevelManager, DEFAULT_LEVEL};
+use crate::scoring::Scores;
+use crate::shooting_gallery::{ShootingGallery, StartGallery, ROUND_DURATION};
+use crate::telemetry::{self, Telemetry, TelemetrySample};
+use crate::vehicle_systems::SuspensionTuning;
+use crate::weapon_def::WeaponDef;
+use crate::weapons::Inventory;
 
 #[derive(Event, Default, Debug)]
 pub struct RespawnEvent;
@@ -16,7 +27,7 @@ impl Plugin for DebugUiPlugin {
         app.add_plugins(EguiPlugin::default())
             .insert_resource(DebugUiState::default())
             .add_event::<RespawnEvent>()
-            .add_systems(EguiContextPass, debug_ui)
+            .add_systems(EguiContextPass, (debug_ui, track_pointer).chain())
             .add_systems(Update, (handle_respawn, toggle_debug_ui));
     }
 }
@@ -24,21 +35,61 @@ impl Plugin for DebugUiPlugin {
 #[derive(Resource)]
 pub struct DebugUiState {
     pub visible: bool,
+    pub preset_name: String,
+    pub presets: Vec<String>,
+    pub preset_status: String,
+    pub level_path: String,
+    pub telemetry_status: String,
 }
 
 impl Default for DebugUiState {
     fn default() -> Self {
-        Self { visible: true }
+        Self {
+            visible: true,
+            preset_name: String::new(),
+            presets: config::list_presets(),
+            preset_status: String::new(),
+            level_path: DEFAULT_LEVEL.to_string(),
+            telemetry_status: String::new(),
+        }
     }
 }
 
+/// The vehicle being driven, whose setup the debug UI edits.
+type DrivenVehicle = (
+    &'static mut Drivetrain,
+    &'static mut Brakes,
+    Option<&'static Health>,
+    Option<&'static mut SuspensionTuning>,
+);
+
+/// A player and what the debug UI shows of it.
+type PlayerStats = (
+    Entity,
+    &'static Player,
+    &'static Transform,
+    Option<&'static Health>,
+    Option<&'static Inventory>,
+);
+
+#[allow(clippy::too_many_arguments)]
 fn debug_ui(
     mut ctxs: EguiContexts,
     mut params: ResMut<GameParams>,
-    players: Query<(&Player, &Transform)>,
+    mut tuning: ResMut<SuspensionTuning>,
+    mut source: ResMut<ConfigSource>,
+    players: Query<PlayerStats>,
+    weapons: Res<Assets<WeaponDef>>,
+    scores: Res<Scores>,
+    mut vehicles: Query<DrivenVehicle, With<Controlled>>,
     time: Res<Time>,
     mut respawn_writer: EventWriter<RespawnEvent>,
-    state: Res<DebugUiState>,
+    mut state: ResMut<DebugUiState>,
+    mut level: ResMut<LevelManager>,
+    asset_server: Res<AssetServer>,
+    mut telemetry: ResMut<Telemetry>,
+    gallery: Res<ShootingGallery>,
+    mut start_gallery: EventWriter<StartGallery>,
 ) {
     if !state.visible {
         return;
@@ -49,6 +100,15 @@ fn debug_ui(
         if ui.button("Respawn").clicked() {
             respawn_writer.write(RespawnEvent);
         }
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut state.level_path);
+            if ui.button("Load level").clicked() {
+                level.load(&asset_server, &state.level_path);
+            }
+            if ui.button("Unload").clicked() {
+                level.unload();
+            }
+        });
         macro_rules! slider {
             ($field:ident, $range:expr) => {
                 ui.add(egui::Slider::new(&mut params.$field, $range).text(stringify!($field)));
@@ -70,48 +130,331 @@ fn debug_ui(
         slider!(mini_map_height, 0.0..=200.0);
         slider!(gravity, 0.0..=30.0);
         slider!(yaw_rate, 0.0..=std::f32::consts::TAU);
-        slider!(fire_rate, 0.1..=20.0);
         slider!(collision_damping, 0.0..=1.0);
         slider!(slope_damping, 0.0..=1.0);
         slider!(slope_ease, 0.1..=2.0);
         slider!(bounce_factor, 0.0..=1.0);
+        slider!(net_send_rate, 1.0..=60.0);
+        slider!(net_interp_delay, 0.0..=0.5);
+        slider!(net_max_extrapolation, 0.0..=1.0);
+        slider!(net_timeout, 1.0..=30.0);
+        slider!(tick_rate, 10.0..=240.0);
+        slider!(vehicle_max_steer, 0.0..=1.0);
+
+        ui.separator();
+        preset_ui(ui, &mut state, &mut params, &mut tuning, &mut source);
     });
 
     egui::Window::new("Player Stats").show(ctx, |ui| {
-        for (i, (p, tf)) in players.iter().enumerate() {
+        for (i, (entity, p, tf, health, inventory)) in players.iter().enumerate() {
             ui.heading(format!("Player {i}"));
             ui.label(format!("speed        : {:>6.2}", p.speed));
             ui.label(format!("vertical_vel : {:>6.2}", p.vertical_vel));
             ui.label(format!("yaw (rad)    : {:>6.2}", p.yaw));
             ui.label(format!("weapon_energy: {:>6.2}", p.weapon_energy));
+            if let Some(def) = inventory.and_then(|i| i.current()).and_then(|h| weapons.get(h)) {
+                ui.label(format!("weapon       : {}", def.name));
+            }
             ui.label(format!("pos          : {:.1?}", tf.translation));
+            if let Some(health) = health {
+                ui.label(format!("health       : {:>6.1}", health.current));
+            }
+            let score = scores.get(entity);
+            ui.label(format!(
+                "score        : {} hits, {:.0} damage, {} kills",
+                score.hits, score.damage, score.kills
+            ));
             ui.separator();
         }
     });
+
+    let mut own_tuning = None;
+    if let Ok((mut drivetrain, mut brakes, health, own)) = vehicles.single_mut() {
+        own_tuning = own;
+        egui::Window::new("Vehicle").show(ctx, |ui| {
+            if let Some(health) = health {
+                ui.add(egui::ProgressBar::new(health.fraction()).text(format!("health {:.0}", health.current)));
+                ui.separator();
+            }
+            drivetrain_ui(ui, &mut drivetrain);
+            ui.separator();
+            brakes_ui(ui, &mut brakes);
+        });
+    }
+
+    egui::Window::new("Suspension").default_open(false).show(ctx, |ui| {
+        // A vehicle's own tuning takes precedence over the global one.
+        if let Some(own) = own_tuning.as_mut() {
+            ui.heading("Driven vehicle");
+            suspension_ui(ui, own);
+            ui.separator();
+            ui.heading("Global");
+        }
+        suspension_ui(ui, &mut tuning);
+    });
+
+    egui::Window::new("Telemetry").default_open(false).show(ctx, |ui| {
+        telemetry_ui(ui, &mut telemetry, &mut state.telemetry_status);
+    });
+
+    egui::Window::new("Shooting Gallery").default_open(false).show(ctx, |ui| {
+        ui.label(format!("score    : {}", gallery.score));
+        ui.label(format!("destroyed: {}", gallery.destroyed));
+        ui.label(format!("best     : {}", gallery.best));
+        match gallery.time_left {
+            Some(time_left) => {
+                ui.label(format!("time left: {time_left:.1}"));
+            }
+            None => {
+                if ui.button(format!("Start {ROUND_DURATION:.0} s round")).clicked() {
+                    start_gallery.write(StartGallery);
+                }
+            }
+        }
+    });
 }
 
+/// Height of each telemetry plot (points).
+const PLOT_HEIGHT: f32 = 80.0;
+/// Line colours of the series of a plot, in order. Wheels use them in the
+/// order they are listed.
+const PLOT_COLORS: [egui::Color32; 4] = [
+    egui::Color32::LIGHT_RED,
+    egui::Color32::LIGHT_GREEN,
+    egui::Color32::LIGHT_BLUE,
+    egui::Color32::YELLOW,
+];
+
+fn telemetry_ui(ui: &mut egui::Ui, telemetry: &mut Telemetry, status: &mut String) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut telemetry.recording, "Record");
+        if ui.button("Clear").clicked() {
+            telemetry.clear();
+        }
+        for extension in ["csv", "json"] {
+            if ui.button(format!("Export {}", extension.to_uppercase())).clicked() {
+                let path = telemetry::export_path(extension);
+                *status = match telemetry.export(&path) {
+                    Ok(()) => format!("saved {}", path.display()),
+                    Err(e) => format!("export failed: {e}"),
+                };
+            }
+        }
+    });
+    ui.label(format!("{} samples", telemetry.samples().len()));
+    if !status.is_empty() {
+        ui.label(status.as_str());
+    }
+
+    let samples = telemetry.samples();
+    let wheels = samples.back().map_or(0, |s| s.wheels.len());
+    let channel = |f: fn(&TelemetrySample) -> f32| samples.iter().map(f).collect::<Vec<_>>();
+    let per_wheel = |f: fn(&telemetry::WheelSample) -> f32| {
+        (0..wheels)
+            .map(|i| samples.iter().map(|s| s.wheels.get(i).map_or(0.0, f)).collect())
+            .collect::<Vec<Vec<f32>>>()
+    };
+    if let Some(last) = samples.back() {
+        let names: Vec<String> = last
+            .wheels
+            .iter()
+            .map(|w| {
+                format!(
+                    "{}{}",
+                    if w.is_front { "F" } else { "R" },
+                    if w.is_left { "L" } else { "R" }
+                )
+            })
+            .collect();
+        ui.horizontal(|ui| {
+            for (name, color) in names.iter().zip(PLOT_COLORS) {
+                ui.colored_label(color, name);
+            }
+        });
+    }
+    plot(ui, "speed (m/s)", &[channel(|s| s.speed)]);
+    plot(ui, "throttle / brake / steer", &[
+        channel(|s| s.throttle),
+        channel(|s| s.brake),
+        channel(|s| s.steer),
+    ]);
+    plot(ui, "compression (m)", &per_wheel(|w| w.compression));
+    plot(ui, "load (N)", &per_wheel(|w| w.load));
+    plot(ui, "longitudinal force (N)", &per_wheel(|w| w.longitudinal_force));
+    plot(ui, "lateral force (N)", &per_wheel(|w| w.lateral_force));
+}
+
+/// Draws each series as a line across the full width, scaled to the range
+/// shared by all of them.
+fn plot(ui: &mut egui::Ui, title: &str, series: &[Vec<f32>]) {
+    ui.label(title);
+    let size = egui::vec2(ui.available_width().max(200.0), PLOT_HEIGHT);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_black_alpha(96));
+    let (min, max) = series
+        .iter()
+        .flatten()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    if min > max {
+        return;
+    }
+    let span = (max - min).max(f32::EPSILON);
+    for (values, color) in series.iter().zip(PLOT_COLORS) {
+        let steps = values.len().saturating_sub(1).max(1) as f32;
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                egui::pos2(
+                    rect.left() + rect.width() * i as f32 / steps,
+                    rect.bottom() - rect.height() * (v - min) / span,
+                )
+            })
+            .collect();
+        painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+    }
+    let font = egui::FontId::monospace(10.0);
+    painter.text(rect.left_top(), egui::Align2::LEFT_TOP, format!("{max:.2}"), font.clone(), egui::Color32::GRAY);
+    painter.text(rect.left_bottom(), egui::Align2::LEFT_BOTTOM, format!("{min:.2}"), font, egui::Color32::GRAY);
+}
+
+fn suspension_ui(ui: &mut egui::Ui, tuning: &mut SuspensionTuning) {
+    ui.add(egui::Slider::new(&mut tuning.k, 1.0e4..=2.0e5).text("spring k (N/m)"));
+    ui.add(egui::Slider::new(&mut tuning.c, 0.0..=2.0e4).text("damping c (N·s/m)"));
+    ui.add(egui::Slider::new(&mut tuning.k_anti_roll, 0.0..=5.0e4).text("anti-roll (N/m)"));
+    ui.add(egui::Slider::new(&mut tuning.rest_length, 0.05..=0.5).text("rest length (m)"));
+    ui.add(egui::Slider::new(&mut tuning.max_travel, 0.0..=0.3).text("max travel (m)"));
+    ui.add(egui::Slider::new(&mut tuning.mu_long, 0.1..=2.0).text("mu long"));
+    ui.add(egui::Slider::new(&mut tuning.mu_lat, 0.1..=2.0).text("mu lat"));
+    ui.checkbox(&mut tuning.gizmo, "Draw gizmos");
+}
+
+fn brakes_ui(ui: &mut egui::Ui, brakes: &mut Brakes) {
+    ui.add(egui::Slider::new(&mut brakes.max_torque, 0.0..=15000.0).text("brake torque"));
+    ui.add(egui::Slider::new(&mut brakes.front_bias, 0.0..=1.0).text("front bias"));
+    ui.add(egui::Slider::new(&mut brakes.handbrake_torque, 0.0..=5000.0).text("handbrake torque"));
+    ui.checkbox(&mut brakes.abs, "ABS");
+}
+
+fn drivetrain_ui(ui: &mut egui::Ui, drivetrain: &mut Drivetrain) {
+    let gear = match drivetrain.gear {
+        -1 => "R".to_string(),
+        0 => "N".to_string(),
+        g => g.to_string(),
+    };
+    ui.label(format!("gear  : {gear}"));
+    ui.label(format!("rpm   : {:>6.0}", drivetrain.rpm));
+    ui.label(format!("clutch: {:>6.2}", drivetrain.clutch));
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut drivetrain.gearbox, Gearbox::Automatic, "Automatic");
+        ui.selectable_value(&mut drivetrain.gearbox, Gearbox::Manual, "Manual");
+    });
+    ui.horizontal(|ui| {
+        for layout in DriveLayout::ALL {
+            ui.selectable_value(&mut drivetrain.layout, layout, format!("{layout:?}"));
+        }
+    });
+    if drivetrain.layout == DriveLayout::Awd {
+        ui.add(egui::Slider::new(&mut drivetrain.front_split, 0.0..=1.0).text("front_split"));
+    }
+    let mut limited_slip = matches!(drivetrain.differential, Differential::LimitedSlip { .. });
+    if ui.checkbox(&mut limited_slip, "Limited-slip differential").changed() {
+        drivetrain.differential = if limited_slip {
+            Differential::LimitedSlip { locking: 0.5 }
+        } else {
+            Differential::Open
+        };
+    }
+    if let Differential::LimitedSlip { locking } = &mut drivetrain.differential {
+        ui.add(egui::Slider::new(locking, 0.0..=1.0).text("locking"));
+    }
+}
+
+fn preset_ui(
+    ui: &mut egui::Ui,
+    state: &mut DebugUiState,
+    params: &mut GameParams,
+    tuning: &mut SuspensionTuning,
+    source: &mut ConfigSource,
+) {
+    let current = || GameConfig {
+        params: params.clone(),
+        suspension: *tuning,
+    };
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut state.preset_name);
+        if ui.button("Save preset").clicked() && !state.preset_name.is_empty() {
+            state.preset_status = match config::save_preset(&state.preset_name, &current()) {
+                Ok(()) => format!("saved {}", state.preset_name),
+                Err(e) => e.to_string(),
+            };
+            state.presets = config::list_presets();
+        }
+    });
+    if ui.button("Save as default").clicked() {
+        state.preset_status = match source.save(&current()) {
+            Ok(()) => format!("saved {}", source.path.display()),
+            Err(e) => e.to_string(),
+        };
+    }
+
+    let mut selected = None;
+    ui.horizontal_wrapped(|ui| {
+        for name in &state.presets {
+            if ui.button(name).clicked() {
+                selected = Some(name.clone());
+            }
+        }
+    });
+    if let Some(name) = selected {
+        state.preset_status = match config::load_preset(&name) {
+            Ok(config) => {
+                *params = config.params;
+                *tuning = config.suspension;
+                state.preset_name = name.clone();
+                format!("loaded {name}")
+            }
+            Err(e) => e.to_string(),
+        };
+    }
+    if !state.preset_status.is_empty() {
+        ui.label(&state.preset_status);
+    }
+}
+
+/// Tells gameplay input whether egui is using the pointer, in any window.
+fn track_pointer(mut ctxs: EguiContexts, mut pointer_over_ui: ResMut<PointerOverUi>) {
+    let wants_pointer = ctxs.ctx_mut().wants_pointer_input();
+    if pointer_over_ui.0 != wants_pointer {
+        pointer_over_ui.0 = wants_pointer;
+    }
+}
+
+/// Respawns the player as falling off the level or dying does, with full
+/// health.
 fn handle_respawn(
     mut ev: EventReader<RespawnEvent>,
-    mut players: Query<(&mut Transform, &mut Player)>,
+    level: Res<LevelManager>,
+    mut players: Query<(&mut Transform, &mut Player, Option<&mut Health>)>,
 ) {
     if ev.is_empty() {
         return;
     }
     ev.clear();
-    for (mut tf, mut plyr) in &mut players {
-        tf.translation = Vec3::new(0.0, 3.0, 0.0);
-        plyr.speed = 0.0;
-        plyr.vertical_vel = 0.0;
-        plyr.fire_timer = 0.0;
-        plyr.weapon_energy = 1.0;
+    for (mut tf, mut plyr, health) in &mut players {
+        respawn_player(Some(&level), &mut tf, &mut plyr);
+        if let Some(mut health) = health {
+            health.restore();
+        }
     }
 }
 
 fn toggle_debug_ui(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut state: ResMut<DebugUiState>,
 ) {
-    if keys.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(Action::ToggleDebug) {
         state.visible = !state.visible;
     }
 }
@@ -0,0 +1,117 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::vehicle::Vehicle;
+
+/// Slower than this (m/s) the car isn't drifting, just turning.
+const DRIFT_MIN_SPEED: f32 = 8.0;
+/// Smallest angle (rad) between heading and travel that counts as a drift.
+const DRIFT_MIN_ANGLE: f32 = 0.26;
+/// Beyond this angle (rad) the car has spun out and the drift is lost.
+const DRIFT_MAX_ANGLE: f32 = 1.75;
+/// A drift survives dropping below the thresholds for this long (s).
+const DRIFT_GRACE: f32 = 0.5;
+/// Drifts shorter than this (s) are not reported.
+const DRIFT_MIN_DURATION: f32 = 0.75;
+
+/// Sent when a vehicle finishes a drift, for game modes to reward.
+#[derive(Event, Clone, Debug)]
+pub struct DriftEvent {
+    pub vehicle: Entity,
+    /// Largest angle between heading and travel (rad).
+    pub angle: f32,
+    /// Seconds spent drifting.
+    pub duration: f32,
+    /// Mean speed while drifting (m/s).
+    pub speed: f32,
+    /// `angle` in degrees times `speed` times `duration`, divided by 10.
+    pub score: f32,
+}
+
+/// Tracks the drift a vehicle is currently in.
+#[derive(Component, Clone, Debug, Default)]
+pub struct DriftState {
+    pub active: bool,
+    pub duration: f32,
+    pub max_angle: f32,
+    distance: f32,
+    grace: f32,
+}
+
+impl DriftState {
+    /// Feeds one tick of travel at `angle` (rad) off the heading at `speed`
+    /// (m/s). Returns the finished drift as `(angle, duration, speed)`.
+    pub fn update(&mut self, angle: f32, speed: f32, dt: f32) -> Option<(f32, f32, f32)> {
+        let angle = angle.abs();
+        if self.active && angle >= DRIFT_MAX_ANGLE {
+            *self = Self::default();
+            return None;
+        }
+        if speed >= DRIFT_MIN_SPEED && (DRIFT_MIN_ANGLE..DRIFT_MAX_ANGLE).contains(&angle) {
+            if !self.active {
+                *self = Self {
+                    active: true,
+                    ..default()
+                };
+            }
+            self.duration += dt;
+            self.distance += speed * dt;
+            self.max_angle = self.max_angle.max(angle);
+            self.grace = 0.0;
+            return None;
+        }
+        if !self.active {
+            return None;
+        }
+        self.grace += dt;
+        if self.grace < DRIFT_GRACE {
+            return None;
+        }
+        let finished = std::mem::take(self);
+        (finished.duration >= DRIFT_MIN_DURATION).then(|| {
+            (
+                finished.max_angle,
+                finished.duration,
+                finished.distance / finished.duration,
+            )
+        })
+    }
+}
+
+/// Plugin detecting and scoring drifts.
+pub struct DriftPlugin;
+
+impl Plugin for DriftPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DriftEvent>().add_systems(
+            FixedUpdate,
+            track_drifts.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn track_drifts(
+    time: Res<Time>,
+    mut vehicles: Query<(Entity, &Transform, &LinearVelocity, &mut DriftState), With<Vehicle>>,
+    mut drifts: EventWriter<DriftEvent>,
+) {
+    let dt = time.delta_secs();
+    for (entity, tf, lin_vel, mut state) in &mut vehicles {
+        let forward = lin_vel.0.dot(tf.rotation * Vec3::Z);
+        let sideways = lin_vel.0.dot(tf.rotation * Vec3::X);
+        let speed = forward.hypot(sideways);
+        let angle = sideways.atan2(forward);
+        if let Some((angle, duration, speed)) = state.update(angle, speed, dt) {
+            let score = angle.to_degrees() * speed * duration / 10.0;
+            info!("drift: {:.0}° for {duration:.1}s at {speed:.0} m/s, {score:.0} points", angle.to_degrees());
+            drifts.write(DriftEvent {
+                vehicle: entity,
+                angle,
+                duration,
+                speed,
+                score,
+            });
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::globals::Controlled;
+use crate::vehicle::Vehicle;
+use crate::vehicle_systems::{compute_tire_forces, RaycastWheel};
+
+/// Converts rad/s to revolutions per minute.
+const RAD_S_TO_RPM: f32 = 60.0 / std::f32::consts::TAU;
+/// Wheel speed difference (rad/s) at which a limited-slip differential
+/// reaches its full locking torque.
+const LSD_SLIP_SPEED: f32 = 1.0;
+
+/// Engine torque (N·m) over RPM, interpolated linearly between points and
+/// held flat outside them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TorqueCurve(pub Vec<(f32, f32)>);
+
+impl Default for TorqueCurve {
+    fn default() -> Self {
+        Self(vec![
+            (1000.0, 200.0),
+            (3000.0, 290.0),
+            (4500.0, 320.0),
+            (6000.0, 290.0),
+            (7000.0, 240.0),
+        ])
+    }
+}
+
+impl TorqueCurve {
+    pub fn torque_at(&self, rpm: f32) -> f32 {
+        let points = &self.0;
+        let Some(&(first_rpm, first)) = points.first() else { return 0.0; };
+        if rpm <= first_rpm {
+            return first;
+        }
+        for pair in points.windows(2) {
+            let ((r0, t0), (r1, t1)) = (pair[0], pair[1]);
+            if rpm <= r1 {
+                return t0 + (t1 - t0) * (rpm - r0) / (r1 - r0).max(f32::EPSILON);
+            }
+        }
+        points.last().map_or(0.0, |&(_, t)| t)
+    }
+}
+
+/// Which axles the engine drives.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriveLayout {
+    Fwd,
+    #[default]
+    Rwd,
+    Awd,
+}
+
+impl DriveLayout {
+    pub const ALL: [DriveLayout; 3] = [DriveLayout::Fwd, DriveLayout::Rwd, DriveLayout::Awd];
+
+    /// Share of the drive torque sent to the front (`true`) or rear axle.
+    /// `front_split` only applies to AWD.
+    pub fn axle_share(self, front: bool, front_split: f32) -> f32 {
+        match (self, front) {
+            (DriveLayout::Fwd, true) | (DriveLayout::Rwd, false) => 1.0,
+            (DriveLayout::Fwd, false) | (DriveLayout::Rwd, true) => 0.0,
+            (DriveLayout::Awd, true) => front_split,
+            (DriveLayout::Awd, false) => 1.0 - front_split,
+        }
+    }
+}
+
+/// How an axle's torque is split between its left and right wheel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Differential {
+    /// Always an even split, so a spinning wheel limits the other.
+    #[default]
+    Open,
+    /// Shifts up to `locking` (0..=1) of the torque towards the slower wheel.
+    LimitedSlip { locking: f32 },
+}
+
+impl Differential {
+    /// Left and right wheel torque for `torque` given their spin rates.
+    pub fn split(self, torque: f32, left_speed: f32, right_speed: f32) -> (f32, f32) {
+        let half = torque * 0.5;
+        match self {
+            Differential::Open => (half, half),
+            Differential::LimitedSlip { locking } => {
+                let slip = ((right_speed - left_speed) / LSD_SLIP_SPEED).clamp(-1.0, 1.0);
+                let bias = locking.clamp(0.0, 1.0) * half.abs() * slip;
+                (half + bias, half - bias)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Gearbox {
+    /// Shifts on RPM and selects reverse when braking from a stop.
+    #[default]
+    Automatic,
+    /// Shifts on [`Action::ShiftUp`]/[`Action::ShiftDown`].
+    Manual,
+}
+
+/// Engine, clutch, gearbox and differentials of a vehicle. Turns the
+/// throttle into drive torque on the wheels of the driven axles. Missing
+/// fields take their default when loaded from a vehicle definition.
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Drivetrain {
+    pub curve: TorqueCurve,
+    pub idle_rpm: f32,
+    /// No torque is produced at or above this speed.
+    pub redline_rpm: f32,
+    /// Engine torque (N·m) resisting rotation at redline with the throttle
+    /// closed, fading to zero at idle.
+    pub engine_braking: f32,
+    /// Forward gear ratios, first gear first.
+    pub gear_ratios: Vec<f32>,
+    pub reverse_ratio: f32,
+    pub final_drive: f32,
+    /// Fraction of engine torque reaching the wheels.
+    pub efficiency: f32,
+    pub layout: DriveLayout,
+    /// Share of torque sent to the front axle with [`DriveLayout::Awd`].
+    pub front_split: f32,
+    pub differential: Differential,
+    pub gearbox: Gearbox,
+    /// Automatic gearbox shifts up above this RPM...
+    pub upshift_rpm: f32,
+    /// ...and down below this one.
+    pub downshift_rpm: f32,
+    /// Seconds the clutch stays open during a shift, and to re-engage it.
+    pub shift_time: f32,
+    /// -1 is reverse, 0 neutral, 1.. forward gears.
+    #[serde(skip)]
+    pub gear: i32,
+    #[serde(skip)]
+    pub rpm: f32,
+    /// 0 disengaged ..=1 fully engaged.
+    #[serde(skip)]
+    pub clutch: f32,
+    /// Seconds left until the clutch starts re-engaging after a shift.
+    #[serde(skip)]
+    pub shift_timer: f32,
+}
+
+impl Default for Drivetrain {
+    fn default() -> Self {
+        Self {
+            curve: TorqueCurve::default(),
+            idle_rpm: 900.0,
+            redline_rpm: 7000.0,
+            engine_braking: 60.0,
+            gear_ratios: vec![3.6, 2.2, 1.5, 1.1, 0.9],
+            reverse_ratio: 3.4,
+            final_drive: 3.9,
+            efficiency: 0.9,
+            layout: DriveLayout::default(),
+            front_split: 0.4,
+            differential: Differential::default(),
+            gearbox: Gearbox::default(),
+            upshift_rpm: 6200.0,
+            downshift_rpm: 2500.0,
+            shift_time: 0.25,
+            gear: 0,
+            rpm: 900.0,
+            clutch: 0.0,
+            shift_timer: 0.0,
+        }
+    }
+}
+
+impl Drivetrain {
+    /// Overall ratio from engine to wheels in `gear`, negative in reverse.
+    pub fn ratio(&self, gear: i32) -> f32 {
+        match gear {
+            0 => 0.0,
+            g if g < 0 => -self.reverse_ratio * self.final_drive,
+            g => self.gear_ratios.get(g as usize - 1).copied().unwrap_or(0.0) * self.final_drive,
+        }
+    }
+
+    pub fn top_gear(&self) -> i32 {
+        self.gear_ratios.len() as i32
+    }
+
+    pub fn is_shifting(&self) -> bool {
+        self.shift_timer > 0.0
+    }
+
+    /// Opens the clutch and selects `gear` if it exists.
+    pub fn shift_to(&mut self, gear: i32) {
+        let gear = gear.clamp(-1, self.top_gear());
+        if gear != self.gear {
+            self.gear = gear;
+            self.shift_timer = self.shift_time;
+            self.clutch = 0.0;
+        }
+    }
+
+    /// Engine speed if the clutch were fully engaged with the wheels
+    /// turning at `wheel_speed` (rad/s).
+    pub fn coupled_rpm(&self, gear: i32, wheel_speed: f32) -> f32 {
+        (wheel_speed * self.ratio(gear)).abs() * RAD_S_TO_RPM
+    }
+
+    /// Advances engine, clutch and automatic gearbox by `dt` seconds and
+    /// returns the total drive torque at the wheels (N·m). `throttle` is
+    /// -1..=1, negative asking an automatic gearbox for reverse, and
+    /// `wheel_speed` the mean spin of the driven wheels (rad/s).
+    pub fn step(&mut self, throttle: f32, wheel_speed: f32, dt: f32) -> f32 {
+        if self.gearbox == Gearbox::Automatic {
+            self.automatic_shift(throttle, wheel_speed);
+        }
+        if self.shift_timer > 0.0 {
+            self.shift_timer -= dt;
+            self.clutch = 0.0;
+        } else {
+            self.clutch = (self.clutch + dt / self.shift_time.max(f32::EPSILON)).min(1.0);
+        }
+
+        let throttle = throttle.abs().min(1.0);
+        let ratio = self.ratio(self.gear);
+        let free_rpm = self.idle_rpm + throttle * (self.redline_rpm - self.idle_rpm);
+        let clutch = if ratio == 0.0 { 0.0 } else { self.clutch };
+        let coupled_rpm = self.coupled_rpm(self.gear, wheel_speed);
+        self.rpm = (free_rpm + (coupled_rpm - free_rpm) * clutch).clamp(self.idle_rpm, self.redline_rpm);
+
+        let drive = if self.rpm >= self.redline_rpm {
+            0.0
+        } else {
+            self.curve.torque_at(self.rpm) * throttle
+        };
+        let rev_range = (self.redline_rpm - self.idle_rpm).max(f32::EPSILON);
+        let braking = self.engine_braking * (1.0 - throttle) * (self.rpm - self.idle_rpm) / rev_range;
+
+        (drive * ratio - braking * ratio.abs() * wheel_speed.signum()) * self.efficiency * clutch
+    }
+
+    fn automatic_shift(&mut self, throttle: f32, wheel_speed: f32) {
+        if throttle < 0.0 && self.gear >= 0 {
+            self.shift_to(-1);
+        } else if throttle > 0.0 && self.gear <= 0 {
+            self.shift_to(1);
+        } else if self.gear >= 1 && !self.is_shifting() {
+            let rpm = self.coupled_rpm(self.gear, wheel_speed);
+            if rpm > self.upshift_rpm && self.gear < self.top_gear() {
+                self.shift_to(self.gear + 1);
+            } else if rpm < self.downshift_rpm && self.gear > 1 {
+                self.shift_to(self.gear - 1);
+            }
+        }
+    }
+}
+
+/// Plugin simulating vehicle drivetrains.
+pub struct DrivetrainPlugin;
+
+impl Plugin for DrivetrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            update_drivetrain
+                .before(compute_tire_forces)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // Shifting reacts to a single press, so like entering vehicles it
+        // runs in `Update` where `just_pressed` is seen exactly once.
+        .add_systems(Update, manual_shift.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Steps each drivetrain and hands its torque to the driven wheels through
+/// the axle differentials.
+pub fn update_drivetrain(
+    time: Res<Time>,
+    mut vehicles: Query<(&Vehicle, &mut Drivetrain, &Children)>,
+    mut wheels: Query<&mut RaycastWheel>,
+) {
+    let dt = time.delta_secs();
+    for (vehicle, mut drivetrain, children) in &mut vehicles {
+        let mut driven_speed = 0.0;
+        let mut driven_count = 0;
+        for child in children.iter() {
+            let Ok(mut wheel) = wheels.get_mut(child) else { continue; };
+            wheel.driven = drivetrain.layout.axle_share(wheel.is_front, drivetrain.front_split) > 0.0;
+            wheel.drive_torque = 0.0;
+            if wheel.driven {
+                driven_speed += wheel.angular_velocity;
+                driven_count += 1;
+            }
+        }
+        if driven_count == 0 {
+            continue;
+        }
+        let torque = drivetrain.step(vehicle.throttle, driven_speed / driven_count as f32, dt);
+
+        for front in [true, false] {
+            let share = drivetrain.layout.axle_share(front, drivetrain.front_split);
+            let mut left = None;
+            let mut right = None;
+            for child in children.iter() {
+                if let Ok(wheel) = wheels.get(child) {
+                    if wheel.is_front == front && wheel.driven {
+                        if wheel.is_left {
+                            left = Some(child);
+                        } else {
+                            right = Some(child);
+                        }
+                    }
+                }
+            }
+            match (left, right) {
+                (Some(left), Some(right)) => {
+                    let Ok([mut left, mut right]) = wheels.get_many_mut([left, right]) else { continue; };
+                    let (l, r) = drivetrain.differential.split(
+                        torque * share,
+                        left.angular_velocity,
+                        right.angular_velocity,
+                    );
+                    left.drive_torque = l;
+                    right.drive_torque = r;
+                }
+                (Some(only), None) | (None, Some(only)) => {
+                    if let Ok(mut wheel) = wheels.get_mut(only) {
+                        wheel.drive_torque = torque * share;
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+fn manual_shift(actions: Res<ActionState>, mut q: Query<&mut Drivetrain, With<Controlled>>) {
+    for mut drivetrain in &mut q {
+        if drivetrain.gearbox != Gearbox::Manual {
+            continue;
+        }
+        let gear = drivetrain.gear;
+        if actions.just_pressed(Action::ShiftUp) {
+            drivetrain.shift_to(gear + 1);
+        } else if actions.just_pressed(Action::ShiftDown) {
+            drivetrain.shift_to(gear - 1);
+        }
+    }
+}
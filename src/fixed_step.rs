@@ -0,0 +1,103 @@
+use bevy::app::RunFixedMainLoopSystem;
+use bevy::prelude::*;
+
+use crate::globals::GameParams;
+
+/// Distance a body may move in one tick before it is treated as a teleport
+/// and snapped instead of interpolated.
+const TELEPORT_DISTANCE: f32 = 5.0;
+
+/// Keeps the `FixedUpdate` rate in sync with [`GameParams::tick_rate`].
+/// All gameplay simulation runs in `FixedUpdate`.
+pub struct FixedStepPlugin;
+
+impl Plugin for FixedStepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_tick_rate.run_if(resource_changed::<GameParams>));
+    }
+}
+
+fn apply_tick_rate(params: Res<GameParams>, mut time: ResMut<Time<Fixed>>) {
+    time.set_timestep_hz(params.tick_rate.max(1.0) as f64);
+}
+
+/// Renders an entity simulated in `FixedUpdate` between its last two
+/// simulated transforms, so motion stays smooth when the frame rate and the
+/// tick rate differ.
+///
+/// Moving the entity outside of `FixedUpdate` (e.g. a respawn) is picked up
+/// as a teleport.
+#[derive(Component, Default)]
+pub struct InterpolatedTransform {
+    previous: Option<Transform>,
+    current: Option<Transform>,
+    rendered: Option<Transform>,
+}
+
+/// Render side of [`InterpolatedTransform`]. Not needed for headless runs,
+/// where `Transform` always holds the simulated state.
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            RunFixedMainLoop,
+            (
+                restore_simulated_transform.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                interpolate_transform.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            ),
+        )
+        .add_systems(FixedFirst, store_previous_transform)
+        .add_systems(FixedLast, store_current_transform);
+    }
+}
+
+/// Puts the simulated transform back before the next ticks run.
+fn restore_simulated_transform(mut q: Query<(&mut Transform, &mut InterpolatedTransform)>) {
+    for (mut tf, mut interp) in &mut q {
+        let (Some(current), Some(rendered)) = (interp.current, interp.rendered) else {
+            continue;
+        };
+        if *tf == rendered {
+            *tf = current;
+        } else {
+            // Moved since the last frame, keep the new transform.
+            interp.previous = Some(*tf);
+            interp.current = Some(*tf);
+        }
+    }
+}
+
+fn store_previous_transform(mut q: Query<(&Transform, &mut InterpolatedTransform)>) {
+    for (tf, mut interp) in &mut q {
+        interp.previous = Some(*tf);
+    }
+}
+
+fn store_current_transform(mut q: Query<(&Transform, &mut InterpolatedTransform)>) {
+    for (tf, mut interp) in &mut q {
+        interp.current = Some(*tf);
+    }
+}
+
+fn interpolate_transform(
+    time: Res<Time<Fixed>>,
+    mut q: Query<(&mut Transform, &mut InterpolatedTransform)>,
+) {
+    let alpha = time.overstep_fraction();
+    for (mut tf, mut interp) in &mut q {
+        let (Some(previous), Some(current)) = (interp.previous, interp.current) else {
+            continue;
+        };
+        *tf = if previous.translation.distance(current.translation) > TELEPORT_DISTANCE {
+            current
+        } else {
+            Transform {
+                translation: previous.translation.lerp(current.translation, alpha),
+                rotation: previous.rotation.slerp(current.rotation, alpha),
+                scale: previous.scale.lerp(current.scale, alpha),
+            }
+        };
+        interp.rendered = Some(*tf);
+    }
+}
@@ -0,0 +1,96 @@
+use avian3d::prelude::{Physics, PhysicsTime};
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::lap_timer::LapCompleted;
+use crate::level::LevelManager;
+
+/// How long the splash screen is shown before the main menu (seconds).
+const SPLASH_DURATION: f32 = 2.0;
+
+/// Top level flow of the game.
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Splash,
+    MainMenu,
+    /// Waiting for the level to finish loading.
+    Loading,
+    Playing,
+    Paused,
+    /// Lap summary shown after crossing the finish.
+    Results,
+}
+
+/// Owns [`GameState`] and the transitions that are not driven by menus.
+/// Gameplay plugins gate their systems with `in_state(GameState::Playing)`.
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .add_event::<LapCompleted>()
+            // State transitions run before `Startup`, so only pause if the
+            // app did not start straight into gameplay.
+            .add_systems(Startup, pause_physics.run_if(not(in_state(GameState::Playing))))
+            .add_systems(OnEnter(GameState::Playing), resume_physics)
+            .add_systems(OnExit(GameState::Playing), pause_physics)
+            .add_systems(
+                Update,
+                (
+                    splash_timer.run_if(in_state(GameState::Splash)),
+                    finish_loading.run_if(in_state(GameState::Loading)),
+                    toggle_pause
+                        .run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))),
+                    show_results.run_if(in_state(GameState::Playing)),
+                ),
+            );
+    }
+}
+
+fn pause_physics(mut time: ResMut<Time<Physics>>) {
+    time.pause();
+}
+
+fn resume_physics(mut time: ResMut<Time<Physics>>) {
+    time.unpause();
+}
+
+fn splash_timer(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut next: ResMut<NextState<GameState>>,
+    mut elapsed: Local<f32>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed >= SPLASH_DURATION || keys.get_just_pressed().next().is_some() {
+        next.set(GameState::MainMenu);
+    }
+}
+
+fn finish_loading(level: Res<LevelManager>, mut next: ResMut<NextState<GameState>>) {
+    if level.current().is_some() && !level.is_loading() {
+        next.set(GameState::Playing);
+    }
+}
+
+fn toggle_pause(
+    actions: Res<ActionState>,
+    state: Res<State<GameState>>,
+    mut next: ResMut<NextState<GameState>>,
+) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    match state.get() {
+        GameState::Playing => next.set(GameState::Paused),
+        GameState::Paused => next.set(GameState::Playing),
+        _ => {}
+    }
+}
+
+fn show_results(mut laps: EventReader<LapCompleted>, mut next: ResMut<NextState<GameState>>) {
+    if laps.read().last().is_some() {
+        next.set(GameState::Results);
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub const PLAYER_HALF_EXTENTS: Vec3 = Vec3::new(5.0, 1.0, 10.0);
 
@@ -8,9 +9,12 @@ pub struct Controlled;
 #[derive(Component)]
 pub struct InVehicle {
     pub vehicle: Entity,
+    /// Index into the vehicle's seats; 0 is the driver.
+    pub seat: usize,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GameParams {
     pub max_speed: f32,
     pub acceleration: f32,
@@ -27,12 +31,19 @@ pub struct GameParams {
     pub brake_acceleration: f32,
     pub gravity: f32,
     pub yaw_rate: f32,
-    pub fire_rate: f32,
     pub collision_damping: f32,
     pub slope_damping: f32,
     pub slope_ease: f32,
     pub bounce_factor: f32,
     pub socket_url: String,
+    pub net_send_rate: f32,
+    pub net_interp_delay: f32,
+    pub net_max_extrapolation: f32,
+    pub net_timeout: f32,
+    /// Simulation steps per second (`FixedUpdate` rate).
+    pub tick_rate: f32,
+    /// Front wheel steering angle at full lock (rad).
+    pub vehicle_max_steer: f32,
 }
 
 impl Default for GameParams {
@@ -53,12 +64,17 @@ impl Default for GameParams {
             mini_map_height: 400.0,
             gravity: 9.81,
             yaw_rate: std::f32::consts::PI / 2.0,
-            fire_rate: 50.0,
             collision_damping: 0.4,
             slope_damping: 0.2,
             slope_ease: 0.5,
             bounce_factor: 0.05,
             socket_url: "wss://535rf3b3kk.execute-api.us-east-1.amazonaws.com/$default".to_string(),
+            net_send_rate: 20.0,
+            net_interp_delay: 0.1,
+            net_max_extrapolation: 0.25,
+            net_timeout: 5.0,
+            tick_rate: 60.0,
+            vehicle_max_steer: 0.6,
         }
     }
 }
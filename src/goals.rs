@@ -1,31 +1,58 @@
 use bevy::prelude::*;
 
+use crate::level::{process_level_requests, LevelDef, LevelEntity, LevelLoaded};
+
 #[derive(Component)]
 pub struct StartGoal;
 
 #[derive(Component)]
 pub struct FinishGoal;
 
+/// Intermediate gate of a lap, numbered in driving order.
+#[derive(Component)]
+pub struct Checkpoint {
+    pub index: usize,
+}
+
 pub struct GoalsPlugin;
 
 impl Plugin for GoalsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_goals);
+        app.add_systems(Update, spawn_level_goals.after(process_level_requests));
     }
 }
 
-fn setup_goals(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let start_scene: Handle<Scene> = asset_server.load("models/start.glb#Scene0");
-    commands
-        .spawn(SceneRoot(start_scene))
-        .insert(StartGoal)
-        .insert(Transform::from_xyz(0.0, 0.0, 0.0))
-        .insert(GlobalTransform::default());
-
-    let finish_scene: Handle<Scene> = asset_server.load("models/finish.glb#Scene0");
-    commands
-        .spawn(SceneRoot(finish_scene))
-        .insert(FinishGoal)
-        .insert(Transform::from_xyz(0.0, 0.0, 50.0))
-        .insert(GlobalTransform::default());
+fn spawn_level_goals(
+    mut commands: Commands,
+    mut events: EventReader<LevelLoaded>,
+    levels: Res<Assets<LevelDef>>,
+    asset_server: Res<AssetServer>,
+) {
+    for ev in events.read() {
+        let Some(level) = levels.get(&ev.handle) else { continue; };
+
+        let start_scene: Handle<Scene> = asset_server.load("models/start.glb#Scene0");
+        commands
+            .spawn(SceneRoot(start_scene))
+            .insert(StartGoal)
+            .insert(level.start.transform())
+            .insert(GlobalTransform::default())
+            .insert(LevelEntity);
+
+        let finish_scene: Handle<Scene> = asset_server.load("models/finish.glb#Scene0");
+        commands
+            .spawn(SceneRoot(finish_scene))
+            .insert(FinishGoal)
+            .insert(level.finish.transform())
+            .insert(GlobalTransform::default())
+            .insert(LevelEntity);
+
+        for (index, checkpoint) in level.checkpoints.iter().enumerate() {
+            commands
+                .spawn(Checkpoint { index })
+                .insert(checkpoint.transform())
+                .insert(GlobalTransform::default())
+                .insert(LevelEntity);
+        }
+    }
 }
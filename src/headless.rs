@@ -0,0 +1,75 @@
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::gltf::GltfPlugin;
+use bevy::input::InputPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::scene::ScenePlugin;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use std::time::Duration;
+
+use crate::actions::BindingsFile;
+use crate::game_state::GameState;
+
+/// Default simulation rate of a headless app (ticks per second), matching
+/// the default `GameParams::tick_rate`.
+pub const DEFAULT_TICK_RATE: f64 = 60.0;
+
+/// Replaces `DefaultPlugins` when running without a window, e.g. as a
+/// dedicated server or in tests. Each update advances time by exactly one
+/// tick, so runs are reproducible regardless of how fast the host is.
+///
+/// Menus are skipped: the app goes straight to loading the level and
+/// returns to [`GameState::Playing`] after a lap. Input bindings stay at
+/// their defaults and aren't read from or written to disk.
+pub struct HeadlessPlugin {
+    /// Ticks per second. Pass the loaded `GameParams::tick_rate` so that
+    /// `FixedUpdate` runs exactly once per update.
+    pub tick_rate: f64,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+        }
+    }
+}
+
+impl HeadlessPlugin {
+    pub fn tick(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.tick_rate.max(f64::EPSILON))
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        let tick = self.tick();
+        app.add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(tick)))
+            .add_plugins((
+                LogPlugin::default(),
+                TransformPlugin,
+                StatesPlugin,
+                InputPlugin,
+                AssetPlugin::default(),
+                ScenePlugin,
+                GltfPlugin::default(),
+            ))
+            // Asset types normally registered by the render plugins. Level
+            // geometry still needs them for colliders.
+            .init_asset::<Mesh>()
+            .init_asset::<StandardMaterial>()
+            .init_asset::<Image>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(tick))
+            .insert_resource(BindingsFile(None))
+            .add_systems(Update, skip_menus.run_if(resource_exists::<State<GameState>>));
+    }
+}
+
+fn skip_menus(state: Res<State<GameState>>, mut next: ResMut<NextState<GameState>>) {
+    match state.get() {
+        GameState::Splash | GameState::MainMenu => next.set(GameState::Loading),
+        GameState::Paused | GameState::Results => next.set(GameState::Playing),
+        GameState::Loading | GameState::Playing => {}
+    }
+}
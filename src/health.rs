@@ -0,0 +1,390 @@
+use avian3d::prelude::{AngularVelocity, Collisions, LinearVelocity};
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::globals::InVehicle;
+use crate::input::{respawn_player, Player};
+use crate::level::LevelManager;
+use crate::vehicle::Vehicle;
+use crate::vehicle_systems::Chassis;
+use crate::weapons::{fire_weapons, projectile_movement_system};
+
+/// Health of the on-foot player.
+pub const PLAYER_HEALTH: f32 = 100.0;
+/// Velocity change from one impact (m/s) that a body shrugs off. Bumping
+/// kerbs and dropping off a ledge stay below it.
+const IMPACT_THRESHOLD: f32 = 6.0;
+/// Damage per m/s of velocity change above the threshold.
+const IMPACT_DAMAGE: f32 = 5.0;
+/// Moves further than this (m) in one tick are teleports, not impacts.
+const IMPACT_TELEPORT_DISTANCE: f32 = 5.0;
+/// Seconds a wrecked vehicle stays wrecked before it respawns.
+const WRECK_RESPAWN_DELAY: f32 = 5.0;
+
+/// Hit points of a vehicle or player.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Removes `amount`, returning true if this emptied it.
+    pub fn damage(&mut self, amount: f32) -> bool {
+        if self.is_depleted() {
+            return false;
+        }
+        self.current = (self.current - amount).max(0.0);
+        self.is_depleted()
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max.max(f32::EPSILON)
+    }
+
+    pub fn restore(&mut self) {
+        self.current = self.max;
+    }
+}
+
+/// Marks a body that shots and impacts damage. Hits on it or any of its
+/// colliders are reported as [`DamageEvent`]s.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Damageable;
+
+/// Scales the damage of hits on this collider or body: below 1 for armour,
+/// above 1 for weak points.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct HitZone {
+    pub multiplier: f32,
+}
+
+/// Damage multiplier of a hit on `collider` of `body`. The collider's own
+/// [`HitZone`] wins over the body's.
+pub fn hit_multiplier(zones: &Query<&HitZone>, collider: Entity, body: Entity) -> f32 {
+    zones
+        .get(collider)
+        .or_else(|_| zones.get(body))
+        .map_or(1.0, |zone| zone.multiplier)
+}
+
+/// Damage dealt to a [`Damageable`] body, sent wherever a hit is detected.
+/// Health, floating text, hit effects, scoring and network replication
+/// each consume it in their own system.
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    /// Who dealt it: the shooter, or the body crashed into.
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: f32,
+    /// Where the hit landed, in world space, if known.
+    pub point: Option<Vec3>,
+    /// Surface normal at `point`, pointing out of the target.
+    pub normal: Option<Vec3>,
+}
+
+/// Sent by [`apply_damage`] for the part of a [`DamageEvent`] that actually
+/// came off a [`Health`]: nothing for occupants of vehicles or the already
+/// destroyed, and at most what was left.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageApplied {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: f32,
+    /// Where the hit landed, in world space, if known.
+    pub point: Option<Vec3>,
+}
+
+/// Health fractions, highest first, at which a body visibly takes more
+/// damage: the hook for deforming its mesh or detaching parts.
+#[derive(Component, Clone, Debug, Default)]
+pub struct DamageStages {
+    pub thresholds: Vec<f32>,
+    /// Stages passed so far.
+    pub reached: usize,
+}
+
+impl DamageStages {
+    pub fn new(thresholds: Vec<f32>) -> Self {
+        Self {
+            thresholds,
+            reached: 0,
+        }
+    }
+
+    /// Number of thresholds at or above `fraction` of health.
+    pub fn stage_at(&self, fraction: f32) -> usize {
+        self.thresholds.iter().filter(|&&t| fraction <= t).count()
+    }
+}
+
+/// Sent by [`apply_damage`] when a body with [`DamageStages`] passes into a
+/// further stage, and with stage 0 when a wreck is repaired on respawn.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DamageStageReached {
+    pub target: Entity,
+    pub stage: usize,
+    /// Where the hit that caused it landed, if known.
+    pub point: Option<Vec3>,
+}
+
+/// Sent when damage empties an entity's [`Health`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DestroyedEvent {
+    pub target: Entity,
+    /// Source of the final hit.
+    pub source: Option<Entity>,
+}
+
+/// A vehicle whose health ran out. It can't be driven and respawns once the
+/// timer finishes.
+#[derive(Component, Debug)]
+pub struct Wrecked {
+    pub timer: Timer,
+}
+
+/// Where a vehicle returns to after being wrecked.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct RespawnPoint(pub Transform);
+
+/// Damage from an impact that changed a body's velocity by `delta_v` (m/s).
+pub fn impact_damage_for(delta_v: f32) -> f32 {
+    (delta_v - IMPACT_THRESHOLD).max(0.0) * IMPACT_DAMAGE
+}
+
+/// Fall speed and position of a kinematic body on the previous tick, for
+/// detecting hard landings. Dynamic bodies are damaged from their contact
+/// impulses instead.
+#[derive(Component, Default, Debug)]
+pub struct Impact {
+    previous: Option<(f32, Vec3)>,
+}
+
+impl Impact {
+    /// Records this tick's `vertical_velocity` and `position` and returns
+    /// the damage of landing since the previous tick. Only downward speed
+    /// lost counts: speed the controller sheds sliding along walls and
+    /// slopes is not an impact.
+    pub fn update(&mut self, vertical_velocity: f32, position: Vec3) -> f32 {
+        let Some((prev_vertical, prev_position)) = self.previous.replace((vertical_velocity, position))
+        else {
+            return 0.0;
+        };
+        if position.distance(prev_position) > IMPACT_TELEPORT_DISTANCE {
+            return 0.0;
+        }
+        impact_damage_for(vertical_velocity.min(0.0) - prev_vertical.min(0.0))
+    }
+}
+
+/// Plugin handling health, damage, wrecks and respawns.
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<DamageApplied>()
+            .add_event::<DamageStageReached>()
+            .add_event::<DestroyedEvent>()
+            .add_systems(
+            FixedUpdate,
+            (
+                vehicle_impacts,
+                player_impacts,
+                apply_damage
+                    .after(vehicle_impacts)
+                    .after(player_impacts)
+                    .after(fire_weapons)
+                    .after(projectile_movement_system),
+                respawn_wrecks.after(apply_damage),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Source, point and normal of the hardest contact `body` is in, with the
+/// normal pointing out of `body`.
+fn hardest_contact(collisions: &Collisions, body: Entity) -> Option<(Entity, Vec3, Vec3)> {
+    collisions
+        .iter()
+        .filter(|pair| pair.body1 == Some(body) || pair.body2 == Some(body))
+        .max_by(|a, b| {
+            a.total_normal_impulse_magnitude()
+                .total_cmp(&b.total_normal_impulse_magnitude())
+        })
+        .and_then(|pair| {
+            let manifold = pair.manifolds.first()?;
+            let point = manifold
+                .points
+                .iter()
+                .max_by(|a, b| a.penetration.total_cmp(&b.penetration))?;
+            // Manifold normals point from the first collider to the second.
+            Some(if pair.body1 == Some(body) {
+                (pair.body2.unwrap_or(pair.collider2), point.point, manifold.normal)
+            } else {
+                (pair.body1.unwrap_or(pair.collider1), point.point, -manifold.normal)
+            })
+        })
+}
+
+/// Damage from a hard impact, located at the contact that caused it.
+fn impact_damage(collisions: &Collisions, target: Entity, amount: f32) -> DamageEvent {
+    let contact = hardest_contact(collisions, target);
+    DamageEvent {
+        source: contact.map(|(source, _, _)| source),
+        target,
+        amount,
+        point: contact.map(|(_, point, _)| point),
+        normal: contact.map(|(_, _, normal)| normal),
+    }
+}
+
+/// Vehicles are damaged by the velocity change their contacts' impulses
+/// caused this tick.
+fn vehicle_impacts(
+    collisions: Collisions,
+    vehicles: Query<(Entity, &Chassis), Without<Wrecked>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (entity, chassis) in &vehicles {
+        let impulse: f32 = collisions
+            .iter()
+            .filter(|pair| pair.body1 == Some(entity) || pair.body2 == Some(entity))
+            .map(|pair| pair.total_normal_impulse_magnitude())
+            .sum();
+        let amount = impact_damage_for(impulse / chassis.mass.max(f32::EPSILON));
+        if amount > 0.0 {
+            damage.write(impact_damage(&collisions, entity, amount));
+        }
+    }
+}
+
+/// On-foot players are moved kinematically and have no contact impulses,
+/// so they are only hurt by hard landings.
+fn player_impacts(
+    collisions: Collisions,
+    mut players: Query<(Entity, &Transform, &Player, &mut Impact), Without<InVehicle>>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for (entity, tf, plyr, mut impact) in &mut players {
+        let amount = impact.update(plyr.vertical_vel, tf.translation);
+        if amount > 0.0 {
+            damage.write(impact_damage(&collisions, entity, amount));
+        }
+    }
+}
+
+/// A body damage is taken off.
+type DamageTarget = (
+    &'static mut Health,
+    Option<&'static mut DamageStages>,
+    Has<Vehicle>,
+    Has<InVehicle>,
+);
+
+/// Takes damage off [`Health`] and reports what it destroys. Vehicles that
+/// run out are wrecked; players respawn with full health. Players riding in
+/// a vehicle are shielded by it.
+#[allow(clippy::too_many_arguments)]
+pub fn apply_damage(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    mut applied: EventWriter<DamageApplied>,
+    mut stages_reached: EventWriter<DamageStageReached>,
+    mut destroyed: EventWriter<DestroyedEvent>,
+    level: Option<Res<LevelManager>>,
+    mut targets: Query<DamageTarget>,
+    mut players: Query<(&mut Transform, &mut Player)>,
+) {
+    for event in events.read() {
+        let Ok((mut health, stages, is_vehicle, in_vehicle)) = targets.get_mut(event.target) else { continue; };
+        if in_vehicle {
+            continue;
+        }
+        let before = health.current;
+        let emptied = health.damage(event.amount);
+        if health.current < before {
+            applied.write(DamageApplied {
+                source: event.source,
+                target: event.target,
+                amount: before - health.current,
+                point: event.point,
+            });
+        }
+        if let Some(mut stages) = stages {
+            let stage = stages.stage_at(health.fraction());
+            if stage > stages.reached {
+                stages.reached = stage;
+                stages_reached.write(DamageStageReached {
+                    target: event.target,
+                    stage,
+                    point: event.point,
+                });
+            }
+        }
+        if !emptied {
+            continue;
+        }
+        destroyed.write(DestroyedEvent {
+            target: event.target,
+            source: event.source,
+        });
+        if is_vehicle {
+            info!("vehicle {:?} wrecked", event.target);
+            commands.entity(event.target).insert(Wrecked {
+                timer: Timer::from_seconds(WRECK_RESPAWN_DELAY, TimerMode::Once),
+            });
+        } else if let Ok((mut tf, mut plyr)) = players.get_mut(event.target) {
+            respawn_player(level.as_deref(), &mut tf, &mut plyr);
+            health.restore();
+        }
+    }
+}
+
+/// A wrecked vehicle waiting to respawn.
+type Wreck = (
+    Entity,
+    &'static mut Wrecked,
+    &'static RespawnPoint,
+    &'static mut Health,
+    Option<&'static mut DamageStages>,
+    &'static mut Transform,
+    &'static mut LinearVelocity,
+    &'static mut AngularVelocity,
+);
+
+fn respawn_wrecks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stages_reached: EventWriter<DamageStageReached>,
+    mut wrecks: Query<Wreck>,
+) {
+    for (entity, mut wrecked, respawn, mut health, stages, mut tf, mut lin_vel, mut ang_vel) in &mut wrecks {
+        if !wrecked.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        *tf = respawn.0;
+        lin_vel.0 = Vec3::ZERO;
+        ang_vel.0 = Vec3::ZERO;
+        health.restore();
+        if let Some(mut stages) = stages {
+            stages.reached = 0;
+            stages_reached.write(DamageStageReached {
+                target: entity,
+                stage: 0,
+                point: None,
+            });
+        }
+        commands.entity(entity).remove::<Wrecked>();
+        info!("vehicle {entity:?} respawned");
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::health::{DamageEvent, DamageStageReached};
+
+/// Seconds a hit spark lasts.
+const SPARK_LIFETIME: f32 = 0.2;
+/// Speed (m/s) a spark flies off the surface at.
+const SPARK_SPEED: f32 = 2.0;
+const SPARK_RADIUS: f32 = 0.08;
+const SPARK_COLOR: [f32; 3] = [8.0, 4.0, 1.0];
+const SPARK_LIGHT_INTENSITY: f32 = 3000.0;
+/// Sparks thrown off per damage stage a body passes into.
+const STAGE_SPARKS: usize = 6;
+
+/// Plugin flashing a spark wherever damage lands.
+pub struct HitEffectsPlugin;
+
+impl Plugin for HitEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_spark_assets).add_systems(
+            Update,
+            (spawn_hit_sparks, spawn_stage_sparks, hit_spark_system)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Mesh and material shared by all sparks.
+#[derive(Resource)]
+struct SparkAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+/// A short-lived glow flying off a hit surface.
+#[derive(Component)]
+pub struct HitSpark {
+    timer: Timer,
+    velocity: Vec3,
+}
+
+fn setup_spark_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let [r, g, b] = SPARK_COLOR;
+    commands.insert_resource(SparkAssets {
+        mesh: meshes.add(Sphere::new(SPARK_RADIUS)),
+        material: materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            emissive: LinearRgba::from(Color::srgb(r, g, b)),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_hit_sparks(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    assets: Res<SparkAssets>,
+) {
+    for event in events.read() {
+        let Some(point) = event.point else { continue; };
+        spawn_spark(&mut commands, &assets, point, event.normal.unwrap_or(Vec3::Y));
+    }
+}
+
+/// Bursts sparks off a body each time it passes into a further damage
+/// stage, more for later stages.
+fn spawn_stage_sparks(
+    mut commands: Commands,
+    mut events: EventReader<DamageStageReached>,
+    assets: Res<SparkAssets>,
+    transforms: Query<&GlobalTransform>,
+) {
+    for event in events.read() {
+        let Some(point) = event
+            .point
+            .or_else(|| transforms.get(event.target).ok().map(|tf| tf.translation()))
+        else {
+            continue;
+        };
+        let count = event.stage * STAGE_SPARKS;
+        for i in 0..count {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            let direction = Quat::from_rotation_y(angle) * Vec3::new(0.0, 1.0, 1.0).normalize();
+            spawn_spark(&mut commands, &assets, point, direction);
+        }
+    }
+}
+
+fn spawn_spark(commands: &mut Commands, assets: &SparkAssets, point: Vec3, direction: Vec3) {
+    commands
+        .spawn(Mesh3d(assets.mesh.clone()))
+        .insert(MeshMaterial3d(assets.material.clone()))
+        .insert(Transform::from_translation(point))
+        .insert(PointLight {
+            intensity: SPARK_LIGHT_INTENSITY,
+            range: 3.0,
+            color: Color::srgb(1.0, 0.6, 0.2),
+            ..default()
+        })
+        .insert(HitSpark {
+            timer: Timer::from_seconds(SPARK_LIFETIME, TimerMode::Once),
+            velocity: direction * SPARK_SPEED,
+        });
+}
+
+/// Shrinks and dims sparks as they fly, then removes them.
+fn hit_spark_system(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut sparks: Query<(Entity, &mut Transform, &mut PointLight, &mut HitSpark)>,
+) {
+    for (entity, mut tf, mut light, mut spark) in &mut sparks {
+        spark.timer.tick(time.delta());
+        let ratio = spark.timer.fraction_remaining();
+        tf.translation += spark.velocity * time.delta_secs();
+        tf.scale = Vec3::splat(ratio.max(0.01));
+        light.intensity = SPARK_LIGHT_INTENSITY * ratio;
+        if spark.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
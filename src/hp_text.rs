@@ -1,5 +1,12 @@
 use bevy::prelude::*;
 
+use crate::game_state::GameState;
+use crate::health::{apply_damage, DamageEvent, Health};
+use crate::targets::destroy_targets;
+
+/// Hit points text floats this far (m) off the surface that was hit.
+const TEXT_OFFSET: f32 = 0.1;
+
 #[derive(Component)]
 pub struct HpText {
     timer: Timer,
@@ -15,7 +22,43 @@ pub struct HpTextPlugin;
 
 impl Plugin for HpTextPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, hp_text_system);
+        app.add_systems(
+            FixedUpdate,
+            // Reads the health left after the hit, before destroyed targets
+            // are despawned.
+            spawn_hp_text
+                .after(apply_damage)
+                .before(destroy_targets)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, hp_text_system);
+    }
+}
+
+/// Shows the remaining hit points where each located hit landed.
+fn spawn_hp_text(
+    mut commands: Commands,
+    mut events: EventReader<DamageEvent>,
+    healths: Query<&Health>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.read() {
+        let Some(point) = event.point else { continue; };
+        let Ok(health) = healths.get(event.target) else { continue; };
+        let font: Handle<Font> = asset_server.load("fonts/Arial.ttf");
+        let text_pos = point + event.normal.unwrap_or(Vec3::Y) * TEXT_OFFSET;
+        commands.spawn((
+            Text2d::new(format!("{} HP", health.current.ceil())),
+            TextFont {
+                font,
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor::WHITE,
+            TextLayout::default(),
+            Transform::from_translation(text_pos),
+            HpText::new(1.0),
+        ));
     }
 }
 
@@ -2,10 +2,13 @@ use bevy::prelude::*;
 use bevy::render::view::{Layer, RenderLayers};
 use bevy_svg::prelude::*;
 
+use crate::shooting_gallery::{GalleryFinished, ShootingGallery};
 use crate::{globals::{GameParams, Controlled}, input::Player, vehicle::Vehicle};
 
 /// All HUD elements are drawn on this render layer.
 pub const HUD_LAYER: u8 = 1;
+/// Seconds the result of a gallery round stays on screen.
+const GALLERY_RESULT_TIME: f32 = 4.0;
 
 /// Plugin that sets up the heads-up display.
 pub struct HudPlugin;
@@ -14,13 +17,31 @@ impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(SvgPlugin)
             .add_systems(Startup, setup_hud)
-            .add_systems(Update, (update_speedometer, position_speedometer));
+            .add_systems(
+                Update,
+                (
+                    update_speedometer,
+                    position_speedometer,
+                    update_gallery_score,
+                    show_gallery_result,
+                ),
+            );
     }
 }
 
 #[derive(Component)]
 struct Speedometer;
 
+/// Shooting gallery score and round time.
+#[derive(Component)]
+struct GalleryScore;
+
+/// Final score of the last gallery round, shown for a while after it ends.
+#[derive(Component)]
+struct GalleryResult {
+    timer: Timer,
+}
+
 fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>, windows: Query<&Window>) {
     // 2D camera for the HUD overlay. Clear color is disabled so the 3d scene
     // remains visible.
@@ -34,8 +55,8 @@ fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>, windows: Qu
         RenderLayers::layer(HUD_LAYER as Layer),
     ));
 
-    let window = windows.single();
-    let win_size = window.unwrap().resolution.physical_size();
+    let Ok(window) = windows.single() else { return; };
+    let win_size = window.resolution.physical_size();
 
     let speedometer = asset_server.load("speedometer.svg");
 
@@ -50,6 +71,32 @@ fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>, windows: Qu
         RenderLayers::layer(HUD_LAYER as Layer),
         Speedometer,
     ));
+
+    commands.spawn((
+        Text2d::default(),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor::WHITE,
+        Transform::default(),
+        RenderLayers::layer(HUD_LAYER as Layer),
+        GalleryScore,
+    ));
+
+    let mut timer = Timer::from_seconds(GALLERY_RESULT_TIME, TimerMode::Once);
+    timer.tick(timer.duration());
+    commands.spawn((
+        Text2d::default(),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor::WHITE,
+        Transform::default(),
+        RenderLayers::layer(HUD_LAYER as Layer),
+        GalleryResult { timer },
+    ));
 }
 
 fn update_speedometer(
@@ -76,10 +123,57 @@ fn update_speedometer(
 }
 
 fn position_speedometer(windows: Query<&Window>, mut q: Query<&mut Transform, With<Speedometer>>) {
-    let window = windows.single();
-    let size = window.unwrap().resolution.physical_size();
+    let Ok(window) = windows.single() else { return; };
+    let size = window.resolution.physical_size();
     for mut tf in &mut q {
         tf.translation.x = -(size.x as f32 / 4.0) + 20.0;
         tf.translation.y = -(size.y as f32 / 4.0) + 120.00;
     }
 }
+
+fn update_gallery_score(
+    gallery: Res<ShootingGallery>,
+    windows: Query<&Window>,
+    mut q: Query<(&mut Text2d, &mut Transform), With<GalleryScore>>,
+) {
+    let Ok(window) = windows.single() else { return; };
+    let size = window.resolution.physical_size();
+    let mut label = format!("Score {}", gallery.score);
+    if let Some(time_left) = gallery.time_left {
+        label += &format!("  {:.0}s", time_left.ceil());
+    }
+    if gallery.best > 0 {
+        label += &format!("  Best {}", gallery.best);
+    }
+    for (mut text, mut tf) in &mut q {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+        tf.translation.x = -(size.x as f32 / 4.0) + 80.0;
+        tf.translation.y = size.y as f32 / 4.0 - 30.0;
+    }
+}
+
+fn show_gallery_result(
+    time: Res<Time>,
+    mut events: EventReader<GalleryFinished>,
+    windows: Query<&Window>,
+    mut q: Query<(&mut Text2d, &mut Transform, &mut GalleryResult)>,
+) {
+    let Ok(window) = windows.single() else { return; };
+    let size = window.resolution.physical_size();
+    let finished = events.read().last().copied();
+    for (mut text, mut tf, mut result) in &mut q {
+        if let Some(finished) = finished {
+            text.0 = format!("Round over: {} points", finished.score);
+            if finished.best {
+                text.0 += "  New best!";
+            }
+            result.timer.reset();
+        }
+        if result.timer.tick(time.delta()).just_finished() {
+            text.0.clear();
+        }
+        tf.translation.y = size.y as f32 / 4.0 - 80.0;
+    }
+}
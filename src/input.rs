@@ -1,18 +1,17 @@
+use crate::actions::{Action, ActionState};
 use crate::globals::GameParams;
-use crate::globals::Controlled;
+use crate::game_state::GameState;
+use crate::globals::{Controlled, InVehicle};
+use crate::level::LevelManager;
 use avian3d::prelude::*;
-use bevy::{
-    input::{keyboard::KeyCode, ButtonInput},
-    log::info,
-    prelude::*,
-};
+use bevy::{log::info, prelude::*};
 
 const STEP_HEIGHT: f32 = 0.25;
 const MAX_SLOPE_COS: f32 = 0.707;
 // Extra distance to keep from geometry when resolving collisions
 // Slightly larger skin helps prevent the player from getting stuck in meshes
 const SKIN: f32 = 0.1;
-// Number of physics sub-steps per frame to improve collision robustness
+// Number of collision sub-steps per tick to improve collision robustness
 const SUBSTEPS: u32 = 4;
 const FALL_RESET_Y: f32 = -100.0;
 const RESPAWN_POS: Vec3 = Vec3::new(0.0, 1.5, 0.0);
@@ -29,31 +28,35 @@ pub struct Player {
     pub weapon_energy: f32,
 }
 
+/// The controlled player while walking, not sitting in a vehicle.
+type OnFoot = (With<Controlled>, Without<InVehicle>);
+
 pub struct PlayerControlPlugin;
 impl Plugin for PlayerControlPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
-            Update,
+            FixedUpdate,
             (
                 player_input_system,
                 player_move_system.after(player_input_system),
                 fall_reset_system,
                 player_orientation_system.after(player_move_system),
-            ),
+            )
+                .run_if(in_state(GameState::Playing)),
         );
     }
 }
 
 fn player_input_system(
     time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     params: Res<GameParams>,
-    mut q: Query<&mut Player, With<Controlled>>,
+    mut q: Query<&mut Player, OnFoot>,
 ) {
     let dt = time.delta_secs();
     for mut plyr in &mut q {
-        update_speed(&keys, &params, &mut plyr, dt);
-        update_yaw(&keys, &params, &mut plyr, dt);
+        update_speed(&actions, &params, &mut plyr, dt);
+        update_yaw(&actions, &params, &mut plyr, dt);
     }
 }
 
@@ -61,7 +64,7 @@ fn player_move_system(
     time: Res<Time>,
     params: Res<GameParams>,
     spatial: SpatialQuery,
-    mut q: Query<(Entity, &mut Transform, &mut Player), With<Controlled>>,
+    mut q: Query<(Entity, &mut Transform, &mut Player), OnFoot>,
 ) {
     let dt = time.delta_secs() / SUBSTEPS as f32;
     for (entity, mut tf, mut plyr) in &mut q {
@@ -79,7 +82,7 @@ fn player_move_system(
 
 fn player_orientation_system(
     spatial: SpatialQuery,
-    mut q: Query<(Entity, &mut Transform, &mut Player), With<Controlled>>,
+    mut q: Query<(Entity, &mut Transform, &mut Player), OnFoot>,
 ) {
     for (entity, mut tf, mut plyr) in &mut q {
         apply_ground_snap(&spatial, entity, &mut tf, &mut plyr);
@@ -87,23 +90,20 @@ fn player_orientation_system(
     }
 }
 
-fn update_speed(keys: &ButtonInput<KeyCode>, params: &GameParams, plyr: &mut Player, dt: f32) {
-    if keys.pressed(KeyCode::ArrowUp) {
-        plyr.speed = (plyr.speed + params.acceleration * dt).min(params.max_speed);
-    } else if keys.pressed(KeyCode::ArrowDown) {
-        plyr.speed = (plyr.speed - params.brake_acceleration * dt).max(-params.max_speed);
+fn update_speed(actions: &ActionState, params: &GameParams, plyr: &mut Player, dt: f32) {
+    let throttle = actions.value(Action::Accelerate);
+    let brake = actions.value(Action::Brake);
+    if throttle > 0.0 {
+        plyr.speed = (plyr.speed + params.acceleration * throttle * dt).min(params.max_speed);
+    } else if brake > 0.0 {
+        plyr.speed = (plyr.speed - params.brake_acceleration * brake * dt).max(-params.max_speed);
     } else {
         plyr.speed = plyr.speed.signum() * (plyr.speed.abs() - params.friction * dt).max(0.0);
     }
 }
 
-fn update_yaw(keys: &ButtonInput<KeyCode>, params: &GameParams, plyr: &mut Player, dt: f32) {
-    if keys.pressed(KeyCode::ArrowLeft) {
-        plyr.yaw += params.yaw_rate * dt;
-    }
-    if keys.pressed(KeyCode::ArrowRight) {
-        plyr.yaw -= params.yaw_rate * dt;
-    }
+fn update_yaw(actions: &ActionState, params: &GameParams, plyr: &mut Player, dt: f32) {
+    plyr.yaw -= params.yaw_rate * actions.value(Action::Steer) * dt;
 }
 
 fn move_horizontal(
@@ -262,17 +262,30 @@ fn orient_to_ground(spatial: &SpatialQuery, entity: Entity, tf: &mut Transform,
     tf.rotation = tf.rotation.slerp(target, ROT_SMOOTH);
 }
 
-fn fall_reset_system(mut q: Query<(&mut Transform, &mut Player)>) {
+fn fall_reset_system(
+    level: Option<Res<LevelManager>>,
+    mut q: Query<(&mut Transform, &mut Player), Without<InVehicle>>,
+) {
     for (mut tf, mut plyr) in &mut q {
         if tf.translation.y < FALL_RESET_Y {
-            info!("respawn");
-            tf.translation = RESPAWN_POS;
-            plyr.speed = 0.0;
-            plyr.vertical_vel = 0.0;
-            plyr.grounded = false;
-            plyr.yaw = RESPAWN_YAW;
-            plyr.fire_timer = 0.0;
-            plyr.weapon_energy = 1.0;
+            respawn_player(level.as_deref(), &mut tf, &mut plyr);
         }
     }
 }
+
+/// Puts a player back at the level's spawn point with its motion and weapon
+/// reset.
+pub fn respawn_player(level: Option<&LevelManager>, tf: &mut Transform, plyr: &mut Player) {
+    let (spawn_pos, spawn_yaw) = level
+        .and_then(|l| l.player_spawn())
+        .map(|p| (Vec3::from_array(p.position), p.yaw))
+        .unwrap_or((RESPAWN_POS, RESPAWN_YAW));
+    info!("respawn");
+    tf.translation = spawn_pos;
+    plyr.speed = 0.0;
+    plyr.vertical_vel = 0.0;
+    plyr.grounded = false;
+    plyr.yaw = spawn_yaw;
+    plyr.fire_timer = 0.0;
+    plyr.weapon_energy = 1.0;
+}
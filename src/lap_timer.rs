@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
-use crate::goals::{FinishGoal, StartGoal};
+use crate::goals::{Checkpoint, FinishGoal, StartGoal};
+use crate::game_state::GameState;
 use crate::globals::Controlled;
 
 pub struct LapTimerPlugin;
@@ -8,28 +9,44 @@ pub struct LapTimerPlugin;
 impl Plugin for LapTimerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(LapTimer::default())
-            .add_systems(Update, lap_timer_system);
+            .add_event::<LapCompleted>()
+            .add_systems(FixedUpdate, lap_timer_system.run_if(in_state(GameState::Playing)));
     }
 }
 
+/// Fired when the controlled entity crosses the finish after starting a lap
+/// and passing every checkpoint in order.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct LapCompleted {
+    pub time: f32,
+    /// True if this lap set a new best time.
+    pub best: bool,
+}
+
 #[derive(Resource, Default)]
 pub struct LapTimer {
     pub last_lap: Option<f32>,
     pub best_lap: Option<f32>,
     running: bool,
-    start_time: f64,
+    /// Time spent playing since the lap started. Pauses don't count.
+    lap_time: f32,
+    /// Index of the checkpoint the running lap has to pass next.
+    next_checkpoint: usize,
     in_start: bool,
     in_finish: bool,
 }
 
 const GOAL_RADIUS: f32 = 2.0;
 
-fn lap_timer_system(
+/// Times laps of the controlled entity from start to finish.
+pub fn lap_timer_system(
     time: Res<Time>,
     mut timer: ResMut<LapTimer>,
+    mut completed: EventWriter<LapCompleted>,
     player_q: Query<&Transform, With<Controlled>>,
     start_q: Query<&Transform, With<StartGoal>>,
     finish_q: Query<&Transform, With<FinishGoal>>,
+    checkpoints: Query<(&Checkpoint, &Transform)>,
 ) {
     let Ok(player_tf) = player_q.single() else { return; };
     let Ok(start_tf) = start_q.single() else { return; };
@@ -41,8 +58,9 @@ fn lap_timer_system(
     if in_start {
         if !timer.in_start {
             timer.running = true;
-            timer.start_time = time.elapsed_secs_f64();
-            info!("Lap started at {:.2} seconds", timer.start_time);
+            timer.lap_time = 0.0;
+            timer.next_checkpoint = 0;
+            info!("Lap started at {:.2} seconds", time.elapsed_secs_f64());
         }
         timer.in_start = true;
     } else {
@@ -50,19 +68,27 @@ fn lap_timer_system(
     }
 
     if timer.running {
+        timer.lap_time += time.delta_secs();
+        let next = timer.next_checkpoint;
+        if checkpoints
+            .iter()
+            .any(|(cp, tf)| cp.index == next && player_pos.distance(tf.translation) < GOAL_RADIUS)
+        {
+            timer.next_checkpoint += 1;
+        }
+        // A lap that skipped part of the course doesn't finish.
+        let course_done = timer.next_checkpoint >= checkpoints.iter().count();
         let in_finish = player_pos.distance(finish_tf.translation) < GOAL_RADIUS;
-        if in_finish && !timer.in_finish {
-            let lap = (time.elapsed_secs_f64() - timer.start_time) as f32;
+        if in_finish && !timer.in_finish && course_done {
+            let lap = timer.lap_time;
             timer.last_lap = Some(lap);
-            if let Some(best) = timer.best_lap {
-                if lap < best {
-                    timer.best_lap = Some(lap);
-                    info!("New best lap: {:.2} seconds", lap);
-                }
-            } else {
+            let best = timer.best_lap.is_none_or(|best| lap < best);
+            if best {
                 timer.best_lap = Some(lap);
+                info!("New best lap: {:.2} seconds", lap);
             }
             timer.running = false;
+            completed.write(LapCompleted { time: lap, best });
         }
         timer.in_finish = in_finish;
     } else {
@@ -0,0 +1,308 @@
+use bevy::asset::{io::Reader, AssetLoader, LoadContext, LoadState};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+use crate::input::Player;
+use crate::vehicle_def::DEFAULT_VEHICLE;
+
+/// Level loaded on startup.
+pub const DEFAULT_LEVEL: &str = "levels/default.level.ron";
+
+/// Position and heading of something placed in a level.
+#[derive(Deserialize, Clone, Copy, Debug, Default)]
+pub struct Placement {
+    pub position: [f32; 3],
+    /// Rotation around the Y axis in radians.
+    #[serde(default)]
+    pub yaw: f32,
+}
+
+impl Placement {
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(Vec3::from_array(self.position))
+            .with_rotation(Quat::from_rotation_y(self.yaw))
+    }
+}
+
+/// A spot on an armoured target that takes extra damage.
+#[derive(Deserialize, Clone, Debug)]
+pub struct WeakPointDef {
+    /// Centre relative to the target.
+    pub position: [f32; 3],
+    #[serde(default = "default_weak_point_radius")]
+    pub radius: f32,
+    /// Damage multiplier of hits on it.
+    #[serde(default = "default_weak_point_multiplier")]
+    pub multiplier: f32,
+}
+
+fn default_weak_point_radius() -> f32 {
+    0.3
+}
+
+fn default_weak_point_multiplier() -> f32 {
+    3.0
+}
+
+/// How a target moves and takes damage.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub enum TargetKind {
+    #[default]
+    Static,
+    /// Walks from point to point of `path`, relative to the placement, and
+    /// back to the first, at `speed` (m/s).
+    Patrol { path: Vec<[f32; 3]>, speed: f32 },
+    /// Stands for `up` seconds, then hides below ground for `down`.
+    PopUp { up: f32, down: f32 },
+    /// Hits on the body deal `armour` times their damage; weak points take
+    /// their own multiplier.
+    Armoured {
+        armour: f32,
+        weak_points: Vec<WeakPointDef>,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct TargetPlacement {
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub yaw: f32,
+    #[serde(default = "default_target_hp")]
+    pub hp: i32,
+    #[serde(default)]
+    pub kind: TargetKind,
+    /// Shooting gallery points for destroying it.
+    #[serde(default = "default_target_points")]
+    pub points: u32,
+    /// Seconds until a destroyed target returns; `None` never brings it back.
+    #[serde(default = "default_target_respawn")]
+    pub respawn: Option<f32>,
+}
+
+fn default_target_hp() -> i32 {
+    100
+}
+
+fn default_target_points() -> u32 {
+    10
+}
+
+fn default_target_respawn() -> Option<f32> {
+    Some(5.0)
+}
+
+impl TargetPlacement {
+    pub fn transform(&self) -> Transform {
+        Placement { position: self.position, yaw: self.yaw }.transform()
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct VehiclePlacement {
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub yaw: f32,
+    /// Path of the `.vehicle.ron` definition to spawn.
+    #[serde(default = "default_vehicle")]
+    pub vehicle: String,
+}
+
+fn default_vehicle() -> String {
+    DEFAULT_VEHICLE.to_string()
+}
+
+impl VehiclePlacement {
+    pub fn transform(&self) -> Transform {
+        Placement { position: self.position, yaw: self.yaw }.transform()
+    }
+}
+
+/// Description of a level, stored as a `.level.ron` asset.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct LevelDef {
+    pub name: String,
+    /// Scene path of the terrain, e.g. `models/terrain.glb#Scene0`.
+    pub terrain: String,
+    /// Texture for the sky dome.
+    #[serde(default)]
+    pub sky: Option<String>,
+    /// Player spawn points; the first one is used for respawning.
+    pub spawn_points: Vec<Placement>,
+    pub start: Placement,
+    pub finish: Placement,
+    #[serde(default)]
+    pub checkpoints: Vec<Placement>,
+    #[serde(default)]
+    pub targets: Vec<TargetPlacement>,
+    #[serde(default)]
+    pub vehicles: Vec<VehiclePlacement>,
+}
+
+#[derive(Debug)]
+pub enum LevelLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for LevelLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelLoaderError::Io(e) => write!(f, "could not read level: {e}"),
+            LevelLoaderError::Ron(e) => write!(f, "could not parse level: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelLoaderError {}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = LevelDef;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(LevelLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(LevelLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Marks entities owned by the current level; they are despawned on unload.
+#[derive(Component)]
+pub struct LevelEntity;
+
+/// Fired once a level has finished loading and its entities should be spawned.
+#[derive(Event, Clone, Debug)]
+pub struct LevelLoaded {
+    pub handle: Handle<LevelDef>,
+}
+
+/// Tracks the active level and handles switching between levels.
+#[derive(Resource, Default)]
+pub struct LevelManager {
+    current: Option<Handle<LevelDef>>,
+    pending: Option<Handle<LevelDef>>,
+    unload_requested: bool,
+    player_spawn: Option<Placement>,
+}
+
+impl LevelManager {
+    /// Starts loading the level at `path`; the current level is unloaded
+    /// once the new one is ready.
+    pub fn load(&mut self, asset_server: &AssetServer, path: &str) {
+        info!("loading level {path}");
+        self.pending = Some(asset_server.load(path.to_string()));
+    }
+
+    /// Despawns the current level.
+    pub fn unload(&mut self) {
+        self.pending = None;
+        self.unload_requested = true;
+    }
+
+    pub fn current(&self) -> Option<&Handle<LevelDef>> {
+        self.current.as_ref()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Where players (re)spawn in the current level.
+    pub fn player_spawn(&self) -> Option<Placement> {
+        self.player_spawn
+    }
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelDef>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<LevelManager>()
+            .add_event::<LevelLoaded>()
+            .add_systems(Startup, load_default_level)
+            .add_systems(
+                Update,
+                (
+                    process_level_requests,
+                    place_players_at_spawn.after(process_level_requests),
+                ),
+            );
+    }
+}
+
+fn load_default_level(mut manager: ResMut<LevelManager>, asset_server: Res<AssetServer>) {
+    manager.load(&asset_server, DEFAULT_LEVEL);
+}
+
+/// Despawns the old level and announces the new one once its asset is ready.
+pub fn process_level_requests(
+    mut commands: Commands,
+    mut manager: ResMut<LevelManager>,
+    levels: Res<Assets<LevelDef>>,
+    asset_server: Res<AssetServer>,
+    level_entities: Query<Entity, With<LevelEntity>>,
+    mut loaded: EventWriter<LevelLoaded>,
+) {
+    if manager.unload_requested {
+        manager.unload_requested = false;
+        manager.current = None;
+        manager.player_spawn = None;
+        for entity in &level_entities {
+            commands.entity(entity).despawn();
+        }
+        info!("level unloaded");
+    }
+
+    let Some(handle) = manager.pending.clone() else { return; };
+    if let LoadState::Failed(err) = asset_server.load_state(&handle) {
+        error!("failed to load level: {err}");
+        manager.pending = None;
+        return;
+    }
+    let Some(level) = levels.get(&handle) else { return; };
+
+    for entity in &level_entities {
+        commands.entity(entity).despawn();
+    }
+    info!("level '{}' loaded", level.name);
+    manager.player_spawn = level.spawn_points.first().copied();
+    manager.current = Some(handle.clone());
+    manager.pending = None;
+    loaded.write(LevelLoaded { handle });
+}
+
+fn place_players_at_spawn(
+    mut events: EventReader<LevelLoaded>,
+    manager: Res<LevelManager>,
+    mut players: Query<(&mut Transform, &mut Player)>,
+) {
+    if events.read().last().is_none() {
+        return;
+    }
+    let Some(spawn) = manager.player_spawn() else { return; };
+    for (mut tf, mut plyr) in &mut players {
+        tf.translation = Vec3::from_array(spawn.position);
+        plyr.yaw = spawn.yaw;
+        plyr.speed = 0.0;
+        plyr.vertical_vel = 0.0;
+    }
+}
@@ -1,18 +1,43 @@
+pub mod actions;
+pub mod brakes;
 pub mod camera;
+pub mod config;
 pub mod debug_ui;
+pub mod drift;
+pub mod drivetrain;
+pub mod fixed_step;
+pub mod game_state;
 pub mod globals;
+pub mod headless;
+pub mod health;
+pub mod hit_effects;
 pub mod input;
 pub mod minimap;
 pub mod hud;
 pub mod weapon_hud;
 pub mod world;
 pub mod sky;
+pub mod suspension_gizmos;
+pub mod tire;
+pub mod turrets;
+pub mod weapon_def;
 pub mod weapons;
 pub mod targets;
+pub mod telemetry;
 pub mod goals;
 pub mod lap_timer;
+pub mod level;
+pub mod menu;
+pub mod protocol;
+pub mod relay;
+pub mod scoring;
+pub mod shooting_gallery;
 pub mod socket_client;
 pub mod chat;
+pub mod net_players;
+pub mod net_targets;
+pub mod plugins;
 pub mod hp_text;
 pub mod vehicle;
+pub mod vehicle_def;
 pub mod vehicle_systems;
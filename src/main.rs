@@ -1,49 +1,34 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use game_demo::camera::CameraPlugin;
-use game_demo::debug_ui::DebugUiPlugin;
-use game_demo::hud::HudPlugin;
+use game_demo::config::{CliArgs, ConfigPlugin};
 use game_demo::globals::GameParams;
-use game_demo::input::PlayerControlPlugin;
-use game_demo::weapons::WeaponPlugin;
-use game_demo::weapon_hud::WeaponHudPlugin;
-use game_demo::minimap::MiniMapPlugin;
-use game_demo::sky::SkyDomePlugin;
-use game_demo::world::WorldPlugin;
-use game_demo::targets::TargetsPlugin;
-use game_demo::goals::GoalsPlugin;
-use game_demo::lap_timer::LapTimerPlugin;
+use game_demo::headless::HeadlessPlugin;
+use game_demo::net_players::NetPlayersPlugin;
+use game_demo::net_targets::NetTargetsPlugin;
+use game_demo::plugins::{ClientPlugins, SimulationPlugins};
 use game_demo::socket_client::SocketClientPlugin;
-use game_demo::vehicle_systems::VehiclePhysicsPlugin;
-use game_demo::chat::ChatPlugin;
-use game_demo::vehicle::VehiclePlugin;
 
 fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins,
-            PhysicsPlugins::default(),
-            PhysicsDebugPlugin::default(),
-        ))
-        .insert_resource(Gravity(Vec3::new(0.0, -9.81, 0.0)))
-        .insert_resource(GameParams::default())
-        .add_plugins((
-            WorldPlugin,
-            VehiclePlugin,
-            VehiclePhysicsPlugin,
-            TargetsPlugin,
-            GoalsPlugin,
-            SkyDomePlugin,
-            PlayerControlPlugin,
-            WeaponPlugin,
-            CameraPlugin,
-            MiniMapPlugin,
-            HudPlugin,
-            WeaponHudPlugin,
-            LapTimerPlugin,
-            SocketClientPlugin,
-            ChatPlugin,
-        ))
-        .add_plugins(DebugUiPlugin)
-        .run();
+    // Errors are reported by `ConfigPlugin` once logging is up.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (cli, _) = CliArgs::parse(args.iter().cloned());
+
+    // Config first, so a headless run ticks at the configured rate.
+    let mut app = App::new();
+    app.add_plugins(ConfigPlugin { args });
+    if cli.headless {
+        let tick_rate = app.world().resource::<GameParams>().tick_rate;
+        app.add_plugins(HeadlessPlugin {
+            tick_rate: tick_rate as f64,
+        });
+    } else {
+        app.add_plugins(DefaultPlugins);
+    }
+    app.insert_resource(Gravity(Vec3::new(0.0, -9.81, 0.0)))
+        .add_plugins(SimulationPlugins)
+        .add_plugins((SocketClientPlugin, NetPlayersPlugin, NetTargetsPlugin));
+    if !cli.headless {
+        app.add_plugins(ClientPlugins);
+    }
+    app.run();
 }
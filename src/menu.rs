@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContextPass, EguiContexts};
+
+use crate::actions::{Action, ActionBindings, BindingCapture, BindingsFile};
+use crate::debug_ui::RespawnEvent;
+use crate::game_state::GameState;
+use crate::lap_timer::LapTimer;
+
+/// Splash, main menu, pause and results screens.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlsMenu>().add_systems(
+            EguiContextPass,
+            (
+                splash_ui.run_if(in_state(GameState::Splash)),
+                main_menu_ui.run_if(in_state(GameState::MainMenu)),
+                loading_ui.run_if(in_state(GameState::Loading)),
+                pause_menu_ui.run_if(in_state(GameState::Paused)),
+                results_ui.run_if(in_state(GameState::Results)),
+                controls_ui.run_if(in_state(GameState::MainMenu).or(in_state(GameState::Paused))),
+            ),
+        );
+    }
+}
+
+/// Whether the controls (rebinding) window is open.
+#[derive(Resource, Default)]
+struct ControlsMenu {
+    open: bool,
+}
+
+fn centered(ctx: &egui::Context, title: &str, add_contents: impl FnOnce(&mut egui::Ui)) {
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, add_contents);
+}
+
+fn splash_ui(mut ctxs: EguiContexts) {
+    let ctx = ctxs.ctx_mut();
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 3.0);
+            ui.heading("Bevy Game");
+            ui.label("press any key");
+        });
+    });
+}
+
+fn main_menu_ui(
+    mut ctxs: EguiContexts,
+    mut next: ResMut<NextState<GameState>>,
+    mut controls: ResMut<ControlsMenu>,
+    mut exit: EventWriter<AppExit>,
+) {
+    centered(ctxs.ctx_mut(), "Main Menu", |ui| {
+        if ui.button("Play").clicked() {
+            next.set(GameState::Loading);
+        }
+        if ui.button("Controls").clicked() {
+            controls.open = true;
+        }
+        if ui.button("Quit").clicked() {
+            exit.write(AppExit::Success);
+        }
+    });
+}
+
+fn loading_ui(mut ctxs: EguiContexts) {
+    centered(ctxs.ctx_mut(), "Loading", |ui| {
+        ui.spinner();
+    });
+}
+
+fn pause_menu_ui(
+    mut ctxs: EguiContexts,
+    mut next: ResMut<NextState<GameState>>,
+    mut respawn: EventWriter<RespawnEvent>,
+    mut controls: ResMut<ControlsMenu>,
+    mut exit: EventWriter<AppExit>,
+) {
+    centered(ctxs.ctx_mut(), "Paused", |ui| {
+        if ui.button("Resume").clicked() {
+            next.set(GameState::Playing);
+        }
+        if ui.button("Respawn").clicked() {
+            respawn.write(RespawnEvent);
+            next.set(GameState::Playing);
+        }
+        if ui.button("Controls").clicked() {
+            controls.open = true;
+        }
+        if ui.button("Main Menu").clicked() {
+            next.set(GameState::MainMenu);
+        }
+        if ui.button("Quit").clicked() {
+            exit.write(AppExit::Success);
+        }
+    });
+}
+
+fn format_lap(lap: Option<f32>) -> String {
+    lap.map_or("--".to_string(), |t| format!("{t:.2} s"))
+}
+
+fn results_ui(
+    mut ctxs: EguiContexts,
+    timer: Res<LapTimer>,
+    mut next: ResMut<NextState<GameState>>,
+) {
+    centered(ctxs.ctx_mut(), "Results", |ui| {
+        ui.label(format!("Lap time: {}", format_lap(timer.last_lap)));
+        ui.label(format!("Best lap: {}", format_lap(timer.best_lap)));
+        if timer.last_lap.is_some() && timer.last_lap == timer.best_lap {
+            ui.label("New best!");
+        }
+        ui.separator();
+        if ui.button("Continue").clicked() {
+            next.set(GameState::Playing);
+        }
+        if ui.button("Main Menu").clicked() {
+            next.set(GameState::MainMenu);
+        }
+    });
+}
+
+fn controls_ui(
+    mut ctxs: EguiContexts,
+    mut controls: ResMut<ControlsMenu>,
+    mut bindings: ResMut<ActionBindings>,
+    file: Res<BindingsFile>,
+    mut capture: ResMut<BindingCapture>,
+) {
+    if !controls.open {
+        return;
+    }
+    let mut open = true;
+    let mut changed = false;
+    egui::Window::new("Controls")
+        .open(&mut open)
+        .collapsible(false)
+        .show(ctxs.ctx_mut(), |ui| {
+            egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(action.label());
+                    let names: Vec<String> =
+                        bindings.get(action).iter().map(|b| b.label()).collect();
+                    ui.label(names.join(", "));
+                    if capture.action == Some(action) {
+                        ui.label(match (action.is_axis(), capture.waiting_for_second_key()) {
+                            (true, false) => "press left key or push right...",
+                            (true, true) => "press right key...",
+                            (false, _) => "press input...",
+                        });
+                    } else if ui.button("Add").clicked() {
+                        capture.start(action);
+                    }
+                    if ui.button("Clear").clicked() {
+                        bindings.clear(action);
+                        changed = true;
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Reset to defaults").clicked() {
+                    *bindings = ActionBindings::default();
+                    changed = true;
+                }
+                if capture.action.is_some() && ui.button("Cancel").clicked() {
+                    capture.cancel();
+                }
+            });
+        });
+
+    if changed {
+        if let Err(e) = file.save(&bindings) {
+            error!("Failed to save bindings: {e}");
+        }
+    }
+    if !open {
+        controls.open = false;
+        capture.cancel();
+    }
+}
@@ -22,8 +22,8 @@ fn setup_minimap_camera(
     windows: Query<&Window, With<PrimaryWindow>>,
     params: Res<GameParams>,
 ) {
-    let window = windows.single();
-    let win_size = window.unwrap().resolution.physical_size();
+    let Ok(window) = windows.single() else { return; };
+    let win_size = window.resolution.physical_size();
     let mut cam = commands.spawn(Camera3d::default());
 
     let size = params.mini_map_size as u32;
@@ -0,0 +1,323 @@
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+use crate::globals::{Controlled, GameParams};
+use crate::input::Player;
+use crate::protocol::{ClientId, NetMessage};
+use crate::socket_client::{NetMessageReceived, SocketClient};
+use crate::vehicle::Vehicle;
+
+/// Number of snapshots kept per remote player.
+const SNAPSHOT_CAPACITY: usize = 32;
+/// How quickly the clock offset estimate drifts back up after a fast packet.
+const OFFSET_RELAX: f64 = 0.05;
+
+/// State of a remote player at a point in the sender's clock.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot {
+    pub time: f64,
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub yaw: f32,
+    pub speed: f32,
+}
+
+/// Time ordered snapshots used for interpolation.
+#[derive(Default, Debug)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl SnapshotBuffer {
+    /// Inserts a snapshot, keeping the buffer sorted and bounded.
+    /// Snapshots older than the oldest buffered one are dropped.
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if let Some(first) = self.snapshots.front() {
+            if snapshot.time < first.time {
+                return;
+            }
+        }
+        let idx = self
+            .snapshots
+            .iter()
+            .rposition(|s| s.time <= snapshot.time)
+            .map_or(0, |i| i + 1);
+        if idx > 0 && self.snapshots[idx - 1].time == snapshot.time {
+            self.snapshots[idx - 1] = snapshot;
+            return;
+        }
+        self.snapshots.insert(idx, snapshot);
+        while self.snapshots.len() > SNAPSHOT_CAPACITY {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Returns the state at `time`, interpolating between the surrounding
+    /// snapshots or extrapolating at most `max_extrapolation` seconds past
+    /// the newest one.
+    pub fn sample(&self, time: f64, max_extrapolation: f64) -> Option<Snapshot> {
+        let first = *self.snapshots.front()?;
+        let last = *self.snapshots.back()?;
+        if time <= first.time {
+            return Some(first);
+        }
+        if time >= last.time {
+            return Some(self.extrapolate(last, time, max_extrapolation));
+        }
+        let next_idx = self.snapshots.iter().position(|s| s.time > time)?;
+        let a = self.snapshots[next_idx - 1];
+        let b = self.snapshots[next_idx];
+        let t = ((time - a.time) / (b.time - a.time)) as f32;
+        Some(Snapshot {
+            time,
+            position: a.position.lerp(b.position, t),
+            rotation: a.rotation.slerp(b.rotation, t),
+            yaw: a.yaw + (b.yaw - a.yaw) * t,
+            speed: a.speed + (b.speed - a.speed) * t,
+        })
+    }
+
+    fn extrapolate(&self, last: Snapshot, time: f64, max_extrapolation: f64) -> Snapshot {
+        let ahead = (time - last.time).min(max_extrapolation) as f32;
+        let velocity = match self.snapshots.len() {
+            0 | 1 => Quat::from_rotation_y(last.yaw) * Vec3::Z * last.speed,
+            n => {
+                let prev = self.snapshots[n - 2];
+                let dt = (last.time - prev.time) as f32;
+                if dt > f32::EPSILON {
+                    (last.position - prev.position) / dt
+                } else {
+                    Vec3::ZERO
+                }
+            }
+        };
+        Snapshot {
+            time,
+            position: last.position + velocity * ahead,
+            ..last
+        }
+    }
+}
+
+/// Proxy entity mirroring another client's controlled entity.
+#[derive(Component)]
+pub struct RemotePlayer {
+    pub client_id: ClientId,
+    pub snapshots: SnapshotBuffer,
+    /// Local clock minus sender clock, estimated from arrival times.
+    clock_offset: Option<f64>,
+    last_heard: f64,
+}
+
+impl RemotePlayer {
+    fn new(client_id: ClientId, now: f64) -> Self {
+        Self {
+            client_id,
+            snapshots: SnapshotBuffer::default(),
+            clock_offset: None,
+            last_heard: now,
+        }
+    }
+
+    /// Records a snapshot received at local time `now`.
+    fn receive(&mut self, snapshot: Snapshot, now: f64) {
+        let sample = now - snapshot.time;
+        // The smallest observed offset is the least delayed packet, so jump
+        // down immediately and only drift up slowly.
+        self.clock_offset = Some(match self.clock_offset {
+            Some(offset) if sample >= offset => offset + (sample - offset) * OFFSET_RELAX,
+            _ => sample,
+        });
+        self.last_heard = now;
+        self.snapshots.push(snapshot);
+    }
+}
+
+/// Maps client ids to their proxy entities.
+#[derive(Resource, Default)]
+pub struct RemotePlayers {
+    pub entities: HashMap<ClientId, Entity>,
+}
+
+pub struct NetPlayersPlugin;
+
+impl Plugin for NetPlayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemotePlayers>()
+            .init_resource::<ProxyAssets>()
+            .add_systems(
+            Update,
+            (
+                broadcast_local_state,
+                receive_remote_state,
+                interpolate_remote_players.after(receive_remote_state),
+                despawn_stale_players,
+            ),
+        );
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn broadcast_local_state(
+    time: Res<Time>,
+    params: Res<GameParams>,
+    client: Res<SocketClient>,
+    controlled: Query<(&Transform, Option<&Player>, Option<&Vehicle>), With<Controlled>>,
+    mut since_last: Local<f32>,
+) {
+    *since_last += time.delta_secs();
+    if *since_last < 1.0 / params.net_send_rate.max(f32::EPSILON) || !client.is_connected() {
+        return;
+    }
+    *since_last = 0.0;
+
+    let Ok((tf, player, vehicle)) = controlled.single() else { return; };
+    let (yaw, speed) = if let Some(p) = player {
+        (p.yaw, p.speed)
+    } else if let Some(v) = vehicle {
+        (v.yaw, v.speed)
+    } else {
+        (0.0, 0.0)
+    };
+    client.send_message(NetMessage::PlayerState {
+        position: tf.translation.to_array(),
+        rotation: tf.rotation.to_array(),
+        yaw,
+        speed,
+        time: time.elapsed_secs_f64(),
+    });
+}
+
+/// Mesh shared by all remote player proxies and a material per hue, made
+/// when first needed.
+#[derive(Resource, Default)]
+struct ProxyAssets {
+    mesh: Option<Handle<Mesh>>,
+    materials: HashMap<u32, Handle<StandardMaterial>>,
+}
+
+impl ProxyAssets {
+    fn get(
+        &mut self,
+        hue: u32,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        let mesh = self
+            .mesh
+            .get_or_insert_with(|| meshes.add(Cuboid::new(0.5, 0.5, 0.5)))
+            .clone();
+        let material = self
+            .materials
+            .entry(hue)
+            .or_insert_with(|| materials.add(Color::hsl(hue as f32, 0.8, 0.5)))
+            .clone();
+        (mesh, material)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn receive_remote_state(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut events: EventReader<NetMessageReceived>,
+    mut remotes: ResMut<RemotePlayers>,
+    mut proxies: Query<&mut RemotePlayer>,
+    mut assets: ResMut<ProxyAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let now = time.elapsed_secs_f64();
+    // Proxies for senders first heard from this frame. They are spawned
+    // after the loop, so later snapshots from the same sender land here.
+    let mut new_proxies: HashMap<ClientId, (RemotePlayer, Snapshot)> = HashMap::new();
+    for ev in events.read() {
+        match ev.message {
+            NetMessage::PlayerState { position, rotation, yaw, speed, time: sent } => {
+                let snapshot = Snapshot {
+                    time: sent,
+                    position: Vec3::from_array(position),
+                    rotation: Quat::from_array(rotation).normalize(),
+                    yaw,
+                    speed,
+                };
+                if let Some(mut proxy) = remotes
+                    .entities
+                    .get(&ev.sender)
+                    .and_then(|&e| proxies.get_mut(e).ok())
+                {
+                    proxy.receive(snapshot, now);
+                    continue;
+                }
+                let (proxy, latest) = new_proxies
+                    .entry(ev.sender)
+                    .or_insert_with(|| (RemotePlayer::new(ev.sender, now), snapshot));
+                proxy.receive(snapshot, now);
+                *latest = snapshot;
+            }
+            NetMessage::Leave => {
+                new_proxies.remove(&ev.sender);
+                if let Some(entity) = remotes.entities.remove(&ev.sender) {
+                    commands.entity(entity).despawn();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (sender, (proxy, latest)) in new_proxies {
+        let (mesh, material) = assets.get((sender % 360) as u32, &mut meshes, &mut materials);
+        let entity = commands
+            .spawn(Mesh3d(mesh))
+            .insert(MeshMaterial3d(material))
+            .insert(Transform::from_translation(latest.position).with_rotation(latest.rotation))
+            .insert(proxy)
+            .id();
+        info!("spawned proxy {:?} for client {}", entity, sender);
+        remotes.entities.insert(sender, entity);
+    }
+}
+
+fn interpolate_remote_players(
+    time: Res<Time>,
+    params: Res<GameParams>,
+    mut q: Query<(&mut Transform, &RemotePlayer)>,
+) {
+    let now = time.elapsed_secs_f64();
+    for (mut tf, proxy) in &mut q {
+        let Some(offset) = proxy.clock_offset else { continue; };
+        let render_time = now - offset - params.net_interp_delay as f64;
+        if let Some(state) = proxy
+            .snapshots
+            .sample(render_time, params.net_max_extrapolation as f64)
+        {
+            tf.translation = state.position;
+            tf.rotation = state.rotation;
+        }
+    }
+}
+
+fn despawn_stale_players(
+    time: Res<Time>,
+    params: Res<GameParams>,
+    mut commands: Commands,
+    mut remotes: ResMut<RemotePlayers>,
+    q: Query<(Entity, &RemotePlayer)>,
+) {
+    let now = time.elapsed_secs_f64();
+    for (entity, proxy) in &q {
+        if now - proxy.last_heard > params.net_timeout as f64 {
+            info!("client {} timed out", proxy.client_id);
+            remotes.entities.remove(&proxy.client_id);
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::game_state::GameState;
+use crate::health::{apply_damage, DamageApplied, DamageEvent, Health};
+use crate::protocol::NetMessage;
+use crate::socket_client::{NetMessageReceived, SocketClient};
+use crate::targets::{destroy_targets, Target};
+
+/// Plugin keeping target hit points in sync between clients. Hits landed
+/// here are broadcast; hits reported by others are replayed as damage.
+pub struct NetTargetsPlugin;
+
+impl Plugin for NetTargetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, receive_target_hits).add_systems(
+            FixedUpdate,
+            // Destroyed targets are gone once despawned, so lethal hits
+            // have to be sent before that.
+            broadcast_target_hits
+                .after(apply_damage)
+                .before(destroy_targets)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Sends the damage local shooters deal to targets, as actually taken off
+/// their health. Hits replayed from the network have no source, so they
+/// aren't echoed back.
+fn broadcast_target_hits(
+    client: Res<SocketClient>,
+    mut events: EventReader<DamageApplied>,
+    targets: Query<(&Target, &Health, &Transform)>,
+) {
+    for event in events.read() {
+        if event.source.is_none() || !client.is_connected() {
+            continue;
+        }
+        let Ok((target, health, tf)) = targets.get(event.target) else { continue; };
+        client.send_message(NetMessage::TargetHit {
+            target: target.id,
+            generation: target.generation,
+            point: event.point.unwrap_or(tf.translation).to_array(),
+            damage: event.amount.round() as i32,
+            hp: health.current.ceil() as i32,
+        });
+    }
+}
+
+/// Damages local targets down to the hit points a peer reported.
+fn receive_target_hits(
+    mut events: EventReader<NetMessageReceived>,
+    targets: Query<(Entity, &Target, &Health)>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    for ev in events.read() {
+        let NetMessage::TargetHit { target: id, generation, point, hp, .. } = ev.message else {
+            continue;
+        };
+        // Hits on a target that has since respawned here are stale.
+        let Some((entity, _, health)) = targets
+            .iter()
+            .find(|(_, t, _)| t.id == id && t.generation == generation)
+        else {
+            continue;
+        };
+        let amount = health.current - hp as f32;
+        if amount > 0.0 {
+            damage.write(DamageEvent {
+                source: None,
+                target: entity,
+                amount,
+                point: Some(Vec3::from_array(point)),
+                normal: None,
+            });
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use avian3d::prelude::*;
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+use crate::actions::ActionsPlugin;
+use crate::brakes::BrakesPlugin;
+use crate::camera::CameraPlugin;
+use crate::chat::ChatPlugin;
+use crate::debug_ui::DebugUiPlugin;
+use crate::drift::DriftPlugin;
+use crate::drivetrain::DrivetrainPlugin;
+use crate::fixed_step::{FixedStepPlugin, InterpolationPlugin};
+use crate::game_state::GameStatePlugin;
+use crate::goals::GoalsPlugin;
+use crate::health::HealthPlugin;
+use crate::hit_effects::HitEffectsPlugin;
+use crate::hp_text::HpTextPlugin;
+use crate::hud::HudPlugin;
+use crate::input::PlayerControlPlugin;
+use crate::lap_timer::LapTimerPlugin;
+use crate::level::LevelPlugin;
+use crate::menu::MenuPlugin;
+use crate::minimap::MiniMapPlugin;
+use crate::scoring::ScoringPlugin;
+use crate::shooting_gallery::ShootingGalleryPlugin;
+use crate::sky::SkyDomePlugin;
+use crate::suspension_gizmos::SuspensionGizmoPlugin;
+use crate::targets::TargetsPlugin;
+use crate::telemetry::TelemetryPlugin;
+use crate::turrets::TurretPlugin;
+use crate::vehicle::VehiclePlugin;
+use crate::vehicle_systems::VehiclePhysicsPlugin;
+use crate::weapon_hud::WeaponHudPlugin;
+use crate::weapons::WeaponPlugin;
+use crate::world::WorldPlugin;
+
+/// Game logic that runs the same with or without a window: physics, level
+/// loading, player and vehicle control, weapons and turrets, targets and lap
+/// timing.
+/// Simulation systems run in `FixedUpdate` at `GameParams::tick_rate`.
+/// Expects [`GameParams`](crate::globals::GameParams) to be inserted, usually
+/// by [`ConfigPlugin`](crate::config::ConfigPlugin).
+pub struct SimulationPlugins;
+
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(PhysicsPlugins::default())
+            .add(FixedStepPlugin)
+            .add(ActionsPlugin)
+            .add(GameStatePlugin)
+            .add(LevelPlugin)
+            .add(WorldPlugin)
+            .add(VehiclePlugin)
+            .add(VehiclePhysicsPlugin)
+            .add(DrivetrainPlugin)
+            .add(BrakesPlugin)
+            .add(DriftPlugin)
+            .add(TelemetryPlugin)
+            .add(TargetsPlugin)
+            .add(ShootingGalleryPlugin)
+            .add(GoalsPlugin)
+            .add(PlayerControlPlugin)
+            .add(WeaponPlugin)
+            .add(TurretPlugin)
+            .add(HealthPlugin)
+            .add(ScoringPlugin)
+            .add(LapTimerPlugin)
+    }
+}
+
+/// Cameras, HUD, menus and debug tools. Requires a window, so it is left out
+/// of headless builds.
+pub struct ClientPlugins;
+
+impl PluginGroup for ClientPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(PhysicsDebugPlugin::default())
+            .add(InterpolationPlugin)
+            .add(SkyDomePlugin)
+            .add(CameraPlugin)
+            .add(MiniMapPlugin)
+            .add(HudPlugin)
+            .add(WeaponHudPlugin)
+            .add(HpTextPlugin)
+            .add(HitEffectsPlugin)
+            .add(ChatPlugin)
+            .add(DebugUiPlugin)
+            .add(SuspensionGizmoPlugin)
+            .add(MenuPlugin)
+    }
+}
@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Wire protocol version spoken by this build.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Oldest protocol version this build still understands.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Identifier a client picks for itself when it connects.
+pub type ClientId = u64;
+
+/// Sender id used for messages that did not come from a game client,
+/// e.g. plain text typed into the web frontend.
+pub const ANONYMOUS_CLIENT: ClientId = 0;
+
+/// Gameplay message exchanged between clients over the socket.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetMessage {
+    /// Announces the range of protocol versions a client supports.
+    Hello { min_version: u32, max_version: u32 },
+    /// A client entered the session.
+    Join { name: String },
+    /// A client is leaving the session.
+    Leave,
+    /// Free text chat line.
+    Chat { text: String },
+    /// Periodic snapshot of the entity a client is controlling.
+    PlayerState {
+        position: [f32; 3],
+        rotation: [f32; 4],
+        yaw: f32,
+        speed: f32,
+        /// Sender clock in seconds when the snapshot was taken.
+        time: f64,
+    },
+    /// A projectile was fired.
+    LaserFired { origin: [f32; 3], velocity: [f32; 3] },
+    /// A target took damage.
+    TargetHit {
+        target: u32,
+        /// Times the target had respawned, so hits on an earlier one are
+        /// ignored.
+        #[serde(default)]
+        generation: u32,
+        point: [f32; 3],
+        damage: i32,
+        hp: i32,
+    },
+    /// A lap was completed.
+    LapCompleted { time: f32, best: bool },
+    /// Any message type this build does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Versioned wrapper placed in the `data` field of a `sendMessage` payload.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Envelope {
+    pub version: u32,
+    pub sender: ClientId,
+    pub message: NetMessage,
+}
+
+impl Envelope {
+    pub fn new(sender: ClientId, message: NetMessage) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            sender,
+            message,
+        }
+    }
+}
+
+/// Reasons an incoming socket frame could not be turned into an [`Envelope`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// The frame was not valid JSON or did not match the envelope layout.
+    Malformed(String),
+    /// The frame carried no `data` field.
+    MissingData,
+    /// The sender speaks a protocol version older than we support.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Malformed(e) => write!(f, "malformed message: {e}"),
+            DecodeError::MissingData => write!(f, "message has no data field"),
+            DecodeError::UnsupportedVersion(v) => {
+                write!(f, "unsupported protocol version {v} (minimum {MIN_PROTOCOL_VERSION})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Serialises an envelope into the `sendMessage` action understood by the
+/// socket backend.
+pub fn encode(envelope: &Envelope) -> String {
+    json!({
+        "action": "sendMessage",
+        "data": envelope,
+    })
+    .to_string()
+}
+
+/// Parses a frame received from the socket.
+///
+/// Plain string payloads are treated as chat from [`ANONYMOUS_CLIENT`] so
+/// older clients and the web frontend keep working. Unknown message types
+/// decode to [`NetMessage::Unknown`] rather than failing.
+pub fn decode(frame: &str) -> Result<Envelope, DecodeError> {
+    let outer: Value =
+        serde_json::from_str(frame).map_err(|e| DecodeError::Malformed(e.to_string()))?;
+    let data = match outer {
+        Value::Object(mut map) => map.remove("data").ok_or(DecodeError::MissingData)?,
+        Value::String(text) => return Ok(legacy_chat(text)),
+        _ => return Err(DecodeError::Malformed("expected an object".to_string())),
+    };
+
+    let envelope_value = match data {
+        Value::String(text) => match serde_json::from_str::<Value>(&text) {
+            Ok(value @ Value::Object(_)) => value,
+            _ => return Ok(legacy_chat(text)),
+        },
+        value => value,
+    };
+
+    let envelope: Envelope = serde_json::from_value(envelope_value)
+        .map_err(|e| DecodeError::Malformed(e.to_string()))?;
+    if envelope.version < MIN_PROTOCOL_VERSION {
+        return Err(DecodeError::UnsupportedVersion(envelope.version));
+    }
+    Ok(envelope)
+}
+
+fn legacy_chat(text: String) -> Envelope {
+    Envelope {
+        version: PROTOCOL_VERSION,
+        sender: ANONYMOUS_CLIENT,
+        message: NetMessage::Chat { text },
+    }
+}
+
+/// Picks the highest protocol version supported by both us and a peer
+/// advertising `min..=max`, or `None` if the ranges do not overlap.
+pub fn negotiate_version(min: u32, max: u32) -> Option<u32> {
+    let low = min.max(MIN_PROTOCOL_VERSION);
+    let high = max.min(PROTOCOL_VERSION);
+    (low <= high).then_some(high)
+}
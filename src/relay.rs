@@ -0,0 +1,118 @@
+use bevy::log::{info, warn};
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::{protocol::Message, Utf8Bytes};
+
+/// Open connections keyed by the id the relay assigned them.
+type Connections = Arc<Mutex<HashMap<u64, UnboundedSender<Message>>>>;
+
+/// WebSocket relay mirroring the `sendMessage` route of the AWS backend:
+/// every `sendMessage` frame is broadcast verbatim to all connections,
+/// including the sender.
+pub struct RelayServer {
+    listener: TcpListener,
+    connections: Connections,
+    next_id: AtomicU64,
+}
+
+impl RelayServer {
+    /// Binds the relay to `addr`. Use port 0 to pick a free port.
+    pub async fn bind(addr: &str) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections until the task is dropped.
+    pub async fn run(self) -> io::Result<()> {
+        loop {
+            let (stream, peer) = self.listener.accept().await?;
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+            let connections = self.connections.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(id, stream, connections).await {
+                    warn!("Connection {id} from {peer} failed: {e}");
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(
+    id: u64,
+    stream: TcpStream,
+    connections: Connections,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    let ws = accept_async(stream).await?;
+    let (mut write, mut read) = ws.split();
+    let (tx, mut rx) = unbounded_channel::<Message>();
+
+    if let Ok(mut conns) = connections.lock() {
+        conns.insert(id, tx.clone());
+        info!("Connection {id} opened ({} total)", conns.len());
+    }
+
+    let writer = tokio::spawn(async move {
+        while let Some(msg) = rx.recv().await {
+            if write.send(msg).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(frame) = read.next().await {
+        match frame {
+            Ok(Message::Text(text)) => route(id, text, &tx, &connections),
+            Ok(Message::Close(_)) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+
+    if let Ok(mut conns) = connections.lock() {
+        conns.remove(&id);
+        info!("Connection {id} closed ({} total)", conns.len());
+    }
+    writer.abort();
+    Ok(())
+}
+
+/// Dispatches a frame on its `action` field like API Gateway route selection.
+fn route(id: u64, text: Utf8Bytes, reply: &UnboundedSender<Message>, connections: &Connections) {
+    let action = serde_json::from_str::<Value>(&text)
+        .ok()
+        .and_then(|v| v.get("action").and_then(Value::as_str).map(str::to_string));
+
+    match action.as_deref() {
+        Some("sendMessage") => {
+            let Ok(mut conns) = connections.lock() else { return; };
+            // Mirror the Lambda's cleanup of stale connections.
+            conns.retain(|other, tx| {
+                let alive = tx.send(Message::Text(text.clone())).is_ok();
+                if !alive {
+                    info!("Stale connection found, deleting: {other}");
+                }
+                alive
+            });
+        }
+        _ => {
+            info!("Default handler for connection {id}: {text}");
+            let _ = reply.send(Message::Text(Utf8Bytes::from_static(
+                r#"{"message":"Hello, world!"}"#,
+            )));
+        }
+    }
+}
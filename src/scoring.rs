@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::game_state::GameState;
+use crate::health::{apply_damage, DamageApplied, DestroyedEvent};
+use crate::turrets::TurretAim;
+use crate::weapons::Inventory;
+
+/// Damage dealt by one shooter or armed vehicle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Score {
+    pub hits: u32,
+    pub damage: f32,
+    /// Things its final hit destroyed.
+    pub kills: u32,
+}
+
+/// Running [`Score`] of every weapon source that has dealt damage, by
+/// entity. Only damage that came off a [`Health`](crate::health::Health)
+/// counts.
+#[derive(Resource, Default, Debug)]
+pub struct Scores(pub HashMap<Entity, Score>);
+
+impl Scores {
+    pub fn get(&self, source: Entity) -> Score {
+        self.0.get(&source).copied().unwrap_or_default()
+    }
+}
+
+/// Entities that deal damage with weapons: players and armed vehicles.
+type WeaponSources = Or<(With<Inventory>, With<TurretAim>)>;
+
+/// Plugin tallying damage and kills per source.
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scores>().add_systems(
+            FixedUpdate,
+            (score_hits, score_kills)
+                .after(apply_damage)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn score_hits(
+    mut scores: ResMut<Scores>,
+    mut events: EventReader<DamageApplied>,
+    shooters: Query<(), WeaponSources>,
+) {
+    for event in events.read() {
+        let Some(source) = event.source.filter(|&s| shooters.contains(s)) else { continue; };
+        let score = scores.0.entry(source).or_default();
+        score.hits += 1;
+        score.damage += event.amount;
+    }
+}
+
+fn score_kills(
+    mut scores: ResMut<Scores>,
+    mut events: EventReader<DestroyedEvent>,
+    shooters: Query<(), WeaponSources>,
+) {
+    for event in events.read() {
+        if let Some(source) = event.source.filter(|&s| shooters.contains(s)) {
+            scores.0.entry(source).or_default().kills += 1;
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use crate::actions::{Action, ActionState};
+use crate::game_state::GameState;
+use crate::health::{apply_damage, DestroyedEvent, Health};
+use crate::targets::{destroy_targets, Target, TargetRespawn};
+
+/// Length of a shooting gallery round (s).
+pub const ROUND_DURATION: f32 = 60.0;
+
+/// Points scored by destroying targets. Outside rounds targets score
+/// freely; a round starts from zero and its final score stays until the
+/// next one starts.
+#[derive(Resource, Default, Debug)]
+pub struct ShootingGallery {
+    pub score: u32,
+    pub destroyed: u32,
+    /// Highest score of a finished round.
+    pub best: u32,
+    /// Seconds left in the running round, if any.
+    pub time_left: Option<f32>,
+    finished: bool,
+}
+
+impl ShootingGallery {
+    /// Starts a round of `duration` seconds from zero.
+    pub fn start(&mut self, duration: f32) {
+        *self = Self {
+            best: self.best,
+            time_left: Some(duration),
+            ..default()
+        };
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.time_left.is_some()
+    }
+
+    /// Adds the points of a destroyed target, unless a round just ended.
+    pub fn award(&mut self, points: u32) {
+        if !self.finished {
+            self.score += points;
+            self.destroyed += 1;
+        }
+    }
+
+    /// Counts down the running round by `dt`. Returns the final score when
+    /// it ends, keeping it until the next round starts.
+    pub fn update(&mut self, dt: f32) -> Option<u32> {
+        let time_left = self.time_left.as_mut()?;
+        *time_left -= dt;
+        if *time_left > 0.0 {
+            return None;
+        }
+        self.time_left = None;
+        self.finished = true;
+        self.best = self.best.max(self.score);
+        Some(self.score)
+    }
+}
+
+/// Starts a [`ROUND_DURATION`] round unless one is running. Every target
+/// is back at full health when it starts.
+#[derive(Event, Clone, Copy, Debug, Default)]
+pub struct StartGallery;
+
+/// Sent when a shooting gallery round ends.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct GalleryFinished {
+    pub score: u32,
+    /// True if the round beat the previous best.
+    pub best: bool,
+}
+
+/// Plugin scoring destroyed targets and timing gallery rounds.
+pub struct ShootingGalleryPlugin;
+
+impl Plugin for ShootingGalleryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShootingGallery>()
+            .add_event::<StartGallery>()
+            .add_event::<GalleryFinished>()
+            .add_systems(
+                Update,
+                (start_gallery_input, start_gallery)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    // Targets are despawned once scored.
+                    award_points.after(apply_damage).before(destroy_targets),
+                    gallery_timer_system,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn start_gallery_input(actions: Res<ActionState>, mut start: EventWriter<StartGallery>) {
+    if actions.just_pressed(Action::StartGallery) {
+        start.write(StartGallery);
+    }
+}
+
+fn start_gallery(
+    mut events: EventReader<StartGallery>,
+    mut gallery: ResMut<ShootingGallery>,
+    mut targets: Query<&mut Health, With<Target>>,
+    mut respawns: Query<&mut TargetRespawn>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    events.clear();
+    if gallery.is_running() {
+        return;
+    }
+    for mut health in &mut targets {
+        health.restore();
+    }
+    // Destroyed targets come back on the next tick.
+    for mut respawn in &mut respawns {
+        let remaining = respawn.timer.remaining();
+        respawn.timer.tick(remaining);
+    }
+    gallery.start(ROUND_DURATION);
+    info!("Shooting gallery round started");
+}
+
+/// Points only go to hits landed here; hits replayed from the network have
+/// no source.
+fn award_points(
+    mut gallery: ResMut<ShootingGallery>,
+    mut events: EventReader<DestroyedEvent>,
+    targets: Query<&Target>,
+) {
+    for event in events.read() {
+        if event.source.is_none() {
+            continue;
+        }
+        if let Ok(target) = targets.get(event.target) {
+            gallery.award(target.placement.points);
+        }
+    }
+}
+
+fn gallery_timer_system(
+    time: Res<Time>,
+    mut gallery: ResMut<ShootingGallery>,
+    mut finished: EventWriter<GalleryFinished>,
+) {
+    let previous_best = gallery.best;
+    if let Some(score) = gallery.update(time.delta_secs()) {
+        info!("Shooting gallery round over: {score} points");
+        finished.write(GalleryFinished {
+            score,
+            best: score > previous_best,
+        });
+    }
+}
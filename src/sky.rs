@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use bevy::math::primitives::Sphere;
 
+use crate::level::{process_level_requests, LevelDef, LevelLoaded};
+
 #[derive(Component)]
 struct SkyDome;
 
@@ -9,7 +11,10 @@ pub struct SkyDomePlugin;
 impl Plugin for SkyDomePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_sky_dome)
-            .add_systems(Update, follow_camera);
+            .add_systems(
+                Update,
+                (follow_camera, apply_level_sky.after(process_level_requests)),
+            );
     }
 }
 
@@ -44,4 +49,20 @@ fn follow_camera(
     for mut tf in &mut sky_q {
         tf.translation = cam_tf.translation();
     }
-}
\ No newline at end of file
+}
+fn apply_level_sky(
+    mut events: EventReader<LevelLoaded>,
+    levels: Res<Assets<LevelDef>>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sky_q: Query<&MeshMaterial3d<StandardMaterial>, With<SkyDome>>,
+) {
+    for ev in events.read() {
+        let Some(sky) = levels.get(&ev.handle).and_then(|l| l.sky.clone()) else { continue; };
+        for handle in &sky_q {
+            if let Some(mat) = materials.get_mut(&handle.0) {
+                mat.base_color_texture = Some(asset_server.load(sky.clone()));
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use bevy::prelude::*;
-use bevy::log::info;
-use serde_json::json;
-use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+use bevy::log::{info, warn};
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::runtime::Runtime;
 use futures_util::{SinkExt, StreamExt};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
@@ -9,26 +11,85 @@ use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use rustls::crypto::{ring::default_provider as ring_provider};
 use tokio_tungstenite::tungstenite::Utf8Bytes;
 use crate::globals::GameParams;
+use crate::protocol::{self, ClientId, Envelope, NetMessage, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+/// First reconnect delay; doubles on every failed attempt.
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+/// Upper bound for the reconnect delay.
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// Outgoing messages kept while offline; the oldest are dropped first.
+const OUTBOX_LIMIT: usize = 256;
+
+/// Connection state of the socket client.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ConnectionStatus {
+    /// No connection has been requested yet.
+    #[default]
+    Idle,
+    /// First connection attempt in progress.
+    Connecting,
+    Connected,
+    /// Waiting before reconnect attempt number `attempt`.
+    Reconnecting { attempt: u32, delay: Duration },
+}
 
 /// Resource holding the Tokio runtime and connection status.
 #[derive(Resource)]
 pub struct SocketClient {
     runtime: Runtime,
-    connected: Arc<AtomicBool>,
+    status: Arc<Mutex<ConnectionStatus>>,
     sender: Option<UnboundedSender<String>>,
     receiver: Option<UnboundedReceiver<String>>,
+    events: Option<UnboundedReceiver<SocketEvent>>,
+    client_id: ClientId,
+    peer_versions: HashMap<ClientId, u32>,
+}
+
+/// Fired for every decoded message sent by another client.
+#[derive(Event, Clone, Debug)]
+pub struct NetMessageReceived {
+    pub sender: ClientId,
+    pub message: NetMessage,
+}
+
+/// Fired whenever the socket (re)connects.
+#[derive(Event, Clone, Debug)]
+pub struct SocketConnected {
+    pub url: String,
+}
+
+/// Fired when an established connection is lost.
+#[derive(Event, Clone, Debug)]
+pub struct SocketDisconnected {
+    pub reason: Option<String>,
+}
+
+/// Fired when a connection attempt fails.
+#[derive(Event, Clone, Debug)]
+pub struct SocketError {
+    pub message: String,
+}
+
+/// Notifications sent from the connection task to the Bevy world.
+enum SocketEvent {
+    Connected(String),
+    Disconnected(Option<String>),
+    Error(String),
 }
 
 impl Default for SocketClient {
     fn default() -> Self {
-        ring_provider()
-            .install_default()
-            .expect("failed to install CryptoProvider");
+        // Several clients may share a process (e.g. in tests); only the
+        // first one gets to install the provider.
+        let _ = ring_provider().install_default();
         Self {
             runtime: Runtime::new().expect("failed to create Tokio runtime"),
-            connected: Arc::new(AtomicBool::new(false)),
+            status: Arc::new(Mutex::new(ConnectionStatus::Idle)),
             sender: None,
             receiver: None,
+            events: None,
+            client_id: rand::random::<ClientId>().max(1),
+            peer_versions: HashMap::new(),
         }
     }
 }
@@ -36,25 +97,38 @@ impl Default for SocketClient {
 impl SocketClient {
     /// Returns true if the client is currently connected.
     pub fn is_connected(&self) -> bool {
-        self.connected.load(Ordering::SeqCst)
+        self.status() == ConnectionStatus::Connected
+    }
+
+    /// Current connection state.
+    pub fn status(&self) -> ConnectionStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Identifier this client stamps on every outgoing message.
+    pub fn client_id(&self) -> ClientId {
+        self.client_id
+    }
+
+    /// Protocol version agreed with `peer`, if it has said hello.
+    pub fn peer_version(&self, peer: ClientId) -> Option<u32> {
+        self.peer_versions.get(&peer).copied()
     }
 
     /// Sends a chat message over the socket using the `sendMessage` action.
     pub fn send(&self, text: String) {
-        if !self.is_connected() {
-            info!("WebSocket is not open");
-            return;
-        }
+        self.send_message(NetMessage::Chat { text });
+    }
 
+    /// Wraps `message` in a versioned envelope and queues it for sending.
+    /// Messages queued while disconnected are delivered after reconnecting.
+    pub fn send_message(&self, message: NetMessage) {
         if let Some(tx) = &self.sender {
-            let payload = json!({
-                "action": "sendMessage",
-                "data": text,
-            })
-            .to_string();
-
+            let payload = protocol::encode(&Envelope::new(self.client_id, message));
             info!("Queueing outgoing message: {}", payload);
             let _ = tx.send(payload);
+        } else {
+            info!("WebSocket is not open");
         }
     }
 
@@ -72,6 +146,32 @@ impl SocketClient {
             None
         }
     }
+
+    /// Starts the connection task for `url`. The task keeps reconnecting with
+    /// exponential backoff until the client is dropped.
+    pub fn connect(&mut self, url: String) {
+        let (tx_in, rx_in) = unbounded_channel::<String>();
+        let (tx_out, rx_out) = unbounded_channel::<String>();
+        let (tx_events, rx_events) = unbounded_channel::<SocketEvent>();
+
+        self.sender = Some(tx_in);
+        self.receiver = Some(rx_out);
+        self.events = Some(rx_events);
+        set_status(&self.status, ConnectionStatus::Connecting);
+
+        let status = self.status.clone();
+        self.runtime
+            .spawn(connection_loop(url, status, rx_in, tx_out, tx_events));
+    }
+}
+
+/// Delay before reconnect attempt `attempt` (starting at 1): exponential
+/// growth capped at [`BACKOFF_MAX`], with jitter in the upper half so many
+/// clients do not reconnect in lockstep.
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let exp = BACKOFF_BASE.saturating_mul(1u32 << attempt.saturating_sub(1).min(16));
+    let capped = exp.min(BACKOFF_MAX);
+    capped.mul_f32(rand::thread_rng().gen_range(0.5..=1.0))
 }
 
 /// Plugin that establishes a WebSocket connection on startup.
@@ -80,51 +180,226 @@ pub struct SocketClientPlugin;
 impl Plugin for SocketClientPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SocketClient>()
-            .add_systems(Startup, connect_socket);
+            .add_event::<NetMessageReceived>()
+            .add_event::<SocketConnected>()
+            .add_event::<SocketDisconnected>()
+            .add_event::<SocketError>()
+            .add_systems(Startup, connect_socket)
+            .add_systems(
+                Update,
+                (
+                    forward_socket_events,
+                    announce_presence.after(forward_socket_events),
+                    dispatch_messages,
+                ),
+            )
+            .add_systems(Last, announce_leave);
     }
 }
 
 fn connect_socket(mut client: ResMut<SocketClient>, params: Res<GameParams>) {
-    let url = params.socket_url.clone();
-    let status = client.connected.clone();
+    client.connect(params.socket_url.clone());
+}
 
-    let (tx_in, mut rx_in) = unbounded_channel::<String>();
-    let (tx_out, rx_out) = unbounded_channel::<String>();
+/// Turns notifications from the connection task into Bevy events.
+fn forward_socket_events(
+    mut client: ResMut<SocketClient>,
+    mut connected: EventWriter<SocketConnected>,
+    mut disconnected: EventWriter<SocketDisconnected>,
+    mut errors: EventWriter<SocketError>,
+) {
+    let Some(rx) = &mut client.events else { return; };
+    while let Ok(ev) = rx.try_recv() {
+        match ev {
+            SocketEvent::Connected(url) => {
+                connected.write(SocketConnected { url });
+            }
+            SocketEvent::Disconnected(reason) => {
+                disconnected.write(SocketDisconnected { reason });
+            }
+            SocketEvent::Error(message) => {
+                errors.write(SocketError { message });
+            }
+        }
+    }
+}
 
-    client.sender = Some(tx_in);
-    client.receiver = Some(rx_out);
+/// Says hello on every (re)connect so peers can negotiate a version.
+fn announce_presence(client: Res<SocketClient>, mut events: EventReader<SocketConnected>) {
+    if events.read().next().is_none() {
+        return;
+    }
+    client.send_message(NetMessage::Hello {
+        min_version: MIN_PROTOCOL_VERSION,
+        max_version: PROTOCOL_VERSION,
+    });
+    client.send_message(NetMessage::Join {
+        name: format!("player-{:04x}", client.client_id() & 0xffff),
+    });
+}
 
-    client.runtime.spawn(async move {
-        match connect_async(&url).await {
-            Ok((ws, _)) => {
-                info!("Socket connected to {}", url);
-                status.store(true, Ordering::SeqCst);
-                let (mut write, mut read) = ws.split();
+/// Lets peers drop our proxy as soon as the app shuts down.
+fn announce_leave(client: Res<SocketClient>, mut exit: EventReader<AppExit>) {
+    if exit.read().next().is_some() {
+        client.send_message(NetMessage::Leave);
+    }
+}
 
-                let send_task = tokio::spawn(async move {
-                    while let Some(msg) = rx_in.recv().await {
-                        if write.send(Message::Text(Utf8Bytes::from(msg))).await.is_err() {
-                            break;
+/// Decodes incoming frames, handles version negotiation and forwards the
+/// rest as [`NetMessageReceived`] events.
+fn dispatch_messages(
+    mut client: ResMut<SocketClient>,
+    mut events: EventWriter<NetMessageReceived>,
+) {
+    while let Some(frame) = client.try_recv() {
+        let envelope = match protocol::decode(&frame) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                warn!("Dropping socket message: {e}");
+                continue;
+            }
+        };
+        if envelope.sender == client.client_id {
+            continue;
+        }
+        match envelope.message {
+            NetMessage::Hello { min_version, max_version } => {
+                match protocol::negotiate_version(min_version, max_version) {
+                    Some(version) => {
+                        let known = client.peer_versions.insert(envelope.sender, version);
+                        if known.is_none() {
+                            client.send_message(NetMessage::Hello {
+                                min_version: MIN_PROTOCOL_VERSION,
+                                max_version: PROTOCOL_VERSION,
+                            });
                         }
                     }
+                    None => warn!(
+                        "Peer {} speaks protocol {}..={}, incompatible with {}..={}",
+                        envelope.sender, min_version, max_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+                    ),
+                }
+            }
+            NetMessage::Unknown => {
+                info!("Ignoring unknown message from {}", envelope.sender);
+            }
+            message => {
+                if let NetMessage::Leave = message {
+                    client.peer_versions.remove(&envelope.sender);
+                }
+                events.write(NetMessageReceived {
+                    sender: envelope.sender,
+                    message,
                 });
+            }
+        }
+    }
+}
 
-                let recv_task = tokio::spawn(async move {
-                    while let Some(Ok(msg)) = read.next().await {
-                        if let Ok(text) = msg.into_text() {
-                            let _ = tx_out.send(text.to_string());
-                        }
-                    }
-                });
 
-                let _ = tokio::join!(send_task, recv_task);
-                status.store(false, Ordering::SeqCst);
+fn set_status(status: &Mutex<ConnectionStatus>, value: ConnectionStatus) {
+    if let Ok(mut s) = status.lock() {
+        *s = value;
+    }
+}
+
+/// Moves everything waiting in the channel into the bounded outbox.
+fn drain_outgoing(rx_in: &mut UnboundedReceiver<String>, outbox: &mut VecDeque<String>) {
+    while let Ok(msg) = rx_in.try_recv() {
+        outbox.push_back(msg);
+    }
+    while outbox.len() > OUTBOX_LIMIT {
+        outbox.pop_front();
+    }
+}
+
+async fn connection_loop(
+    url: String,
+    status: Arc<Mutex<ConnectionStatus>>,
+    mut rx_in: UnboundedReceiver<String>,
+    tx_out: UnboundedSender<String>,
+    events: UnboundedSender<SocketEvent>,
+) {
+    let mut outbox = VecDeque::new();
+    let mut attempt = 0;
+    loop {
+        match connect_async(&url).await {
+            Ok((ws, _)) => {
+                info!("Socket connected to {}", url);
+                attempt = 0;
+                set_status(&status, ConnectionStatus::Connected);
+                let _ = events.send(SocketEvent::Connected(url.clone()));
+
+                let reason = run_session(ws, &mut rx_in, &tx_out, &mut outbox).await;
                 info!("Socket disconnected");
+                let _ = events.send(SocketEvent::Disconnected(reason));
             }
             Err(e) => {
-                eprintln!("Socket connection error: {e}");
+                warn!("Socket connection error: {e}");
+                let _ = events.send(SocketEvent::Error(e.to_string()));
             }
         }
-    });
+
+        // Both ends of the game side are gone, nothing left to reconnect for.
+        if tx_out.is_closed() {
+            return;
+        }
+
+        attempt += 1;
+        let delay = backoff_delay(attempt);
+        info!("Reconnecting in {:.1?} (attempt {})", delay, attempt);
+        set_status(&status, ConnectionStatus::Reconnecting { attempt, delay });
+        tokio::time::sleep(delay).await;
+        drain_outgoing(&mut rx_in, &mut outbox);
+    }
 }
 
+/// Pumps messages over an open socket until it closes. Unsent messages stay
+/// in `outbox` for the next session. Returns the disconnect reason, if any.
+async fn run_session<S>(
+    ws: tokio_tungstenite::WebSocketStream<S>,
+    rx_in: &mut UnboundedReceiver<String>,
+    tx_out: &UnboundedSender<String>,
+    outbox: &mut VecDeque<String>,
+) -> Option<String>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut write, mut read) = ws.split();
+
+    drain_outgoing(rx_in, outbox);
+    while let Some(msg) = outbox.pop_front() {
+        if let Err(e) = write.send(Message::Text(Utf8Bytes::from(msg.clone()))).await {
+            outbox.push_front(msg);
+            return Some(e.to_string());
+        }
+    }
+
+    loop {
+        tokio::select! {
+            outgoing = rx_in.recv() => {
+                let Some(msg) = outgoing else {
+                    let _ = write.close().await;
+                    return None;
+                };
+                if let Err(e) = write.send(Message::Text(Utf8Bytes::from(msg.clone()))).await {
+                    outbox.push_front(msg);
+                    return Some(e.to_string());
+                }
+            }
+            incoming = read.next() => {
+                match incoming {
+                    Some(Ok(Message::Close(frame))) => {
+                        return frame.map(|f| f.reason.to_string());
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        let _ = tx_out.send(text.to_string());
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Some(e.to_string()),
+                    None => return None,
+                }
+            }
+        }
+    }
+}
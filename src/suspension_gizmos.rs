@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::vehicle_systems::{contact_axes, Chassis, RaycastWheel, SuspensionTuning};
+
+/// Length of a drawn force arrow per newton (m/N).
+const FORCE_SCALE: f32 = 1.0 / 5000.0;
+/// Wheel load (N) drawn fully red; lighter loads shade towards green.
+const FULL_LOAD: f32 = 10_000.0;
+/// Length of the drawn contact normal (m).
+const NORMAL_LENGTH: f32 = 0.3;
+const CONTACT_RADIUS: f32 = 0.05;
+const AIRBORNE_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const LONGITUDINAL_COLOR: Color = Color::srgb(0.2, 0.4, 1.0);
+const LATERAL_COLOR: Color = Color::srgb(1.0, 0.2, 1.0);
+
+/// Plugin drawing suspension rays and forces of vehicles whose
+/// [`SuspensionTuning::gizmo`] is set, or of all vehicles when the global
+/// tuning's is.
+pub struct SuspensionGizmoPlugin;
+
+impl Plugin for SuspensionGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_suspension_gizmos);
+    }
+}
+
+/// Draws each wheel's ray and contact, coloured by load, with arrows for the
+/// spring/damper force and the longitudinal and lateral tire forces.
+/// Airborne wheels only show their ray, in grey.
+fn draw_suspension_gizmos(
+    mut gizmos: Gizmos,
+    global: Res<SuspensionTuning>,
+    chassis_q: Query<(&GlobalTransform, Option<&SuspensionTuning>, &Children), With<Chassis>>,
+    wheels: Query<&RaycastWheel>,
+) {
+    for (chassis_tf, tuning, children) in &chassis_q {
+        let tuning = tuning.unwrap_or(&global);
+        if !tuning.gizmo && !global.gizmo {
+            continue;
+        }
+        let rotation = chassis_tf.rotation();
+        let down = rotation * Vec3::NEG_Y;
+        for child in children.iter() {
+            let Ok(wheel) = wheels.get(child) else { continue; };
+            let origin = chassis_tf.transform_point(wheel.mount);
+            if !wheel.grounded {
+                let end = origin + down * (tuning.rest_length + tuning.max_travel);
+                gizmos.line(origin, end, AIRBORNE_COLOR);
+                continue;
+            }
+
+            let load = (wheel.load / FULL_LOAD).clamp(0.0, 1.0);
+            let color = Color::srgb(load, 1.0 - load, 0.0);
+            let contact = wheel.contact_point;
+            gizmos.line(origin, contact, color);
+            gizmos.sphere(Isometry3d::from_translation(contact), CONTACT_RADIUS, color);
+            gizmos.line(contact, contact + wheel.contact_normal * NORMAL_LENGTH, Color::WHITE);
+            gizmos.arrow(
+                contact,
+                contact + wheel.contact_normal * wheel.suspension_force * FORCE_SCALE,
+                color,
+            );
+
+            let (forward, side) = contact_axes(rotation, wheel);
+            let tire = wheel.tire_force;
+            gizmos.arrow(contact, contact + forward * tire.longitudinal * FORCE_SCALE, LONGITUDINAL_COLOR);
+            gizmos.arrow(contact, contact + side * tire.lateral * FORCE_SCALE, LATERAL_COLOR);
+        }
+    }
+}
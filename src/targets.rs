@@ -1,144 +1,270 @@
 use bevy::prelude::*;
 
-use avian3d::prelude::{ColliderConstructor, ColliderConstructorHierarchy, RigidBody};
+use avian3d::prelude::{Collider, ColliderConstructor, ColliderConstructorHierarchy, RigidBody};
 
-use crate::hp_text::{HpText, HpTextPlugin};
-use crate::weapons::Laser;
+use crate::game_state::GameState;
+use crate::health::{apply_damage, Damageable, DestroyedEvent, Health, HitZone};
+use crate::level::{
+    process_level_requests, LevelDef, LevelEntity, LevelLoaded, TargetKind, TargetPlacement,
+};
 
-#[derive(Component)]
+/// How far (m) a pop-up target sinks when it hides.
+const POP_UP_DEPTH: f32 = 3.0;
+/// Speed (m/s) pop-up targets rise and sink at.
+const POP_UP_SPEED: f32 = 6.0;
+/// Patrolling targets move on to the next point within this distance (m).
+const PATROL_REACHED: f32 = 0.05;
+
+/// A shootable target placed by the level. Its hit points are a [`Health`].
+///
+/// Only hits are shared between clients. Patrols, pop-ups and respawn
+/// timers run locally, so moving targets may be out of step on each client.
+#[derive(Component, Debug)]
 pub struct Target {
-    hp: i32,
-    half_extents: Vec3,
+    /// Index of its placement in the level, the same on every client.
+    pub id: u32,
+    /// Times it has respawned since the level loaded.
+    pub generation: u32,
+    pub placement: TargetPlacement,
+}
+
+/// Moves a target along a loop of points.
+#[derive(Component, Debug)]
+pub struct Patrol {
+    /// World positions, visited in order.
+    pub path: Vec<Vec3>,
+    pub speed: f32,
+    pub next: usize,
+}
+
+/// Raises and hides a target on a timer.
+#[derive(Component, Debug)]
+pub struct PopUp {
+    pub up: f32,
+    pub down: f32,
+    pub raised: bool,
+    /// Height of the target when raised.
+    base: f32,
+    timer: Timer,
 }
 
-impl Target {
-    pub fn new(hp: i32, half_extents: Vec3) -> Self {
-        Self { hp, half_extents }
+impl PopUp {
+    pub fn new(up: f32, down: f32, base: f32) -> Self {
+        Self {
+            up,
+            down,
+            raised: true,
+            base,
+            timer: Timer::from_seconds(up, TimerMode::Once),
+        }
+    }
+
+    /// Advances the timer by `dt`, switching between raised and hidden, and
+    /// returns the height the target is heading for.
+    pub fn update(&mut self, dt: f32) -> f32 {
+        if self.timer.tick(std::time::Duration::from_secs_f32(dt)).finished() {
+            self.raised = !self.raised;
+            let duration = if self.raised { self.up } else { self.down };
+            self.timer = Timer::from_seconds(duration, TimerMode::Once);
+        }
+        if self.raised {
+            self.base
+        } else {
+            self.base - POP_UP_DEPTH
+        }
     }
 }
 
+/// Brings a destroyed target back once the timer finishes. Belongs to the
+/// level, so unloading cancels it.
+#[derive(Component, Debug)]
+pub struct TargetRespawn {
+    pub id: u32,
+    /// Generation of the target that was destroyed.
+    pub generation: u32,
+    pub placement: TargetPlacement,
+    pub timer: Timer,
+}
+
 pub struct TargetsPlugin;
 
 impl Plugin for TargetsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(HpTextPlugin)
-            .add_systems(Startup, spawn_target)
+        app.add_systems(Update, spawn_level_targets.after(process_level_requests))
             .add_systems(
-                Update,
-                laser_hit_system.after(crate::weapons::laser_movement_system),
+                FixedUpdate,
+                (
+                    patrol_targets,
+                    pop_up_targets,
+                    destroy_targets.after(apply_damage),
+                    respawn_targets,
+                )
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
 
-const TARGET_HALF_EXTENTS: Vec3 = Vec3::new(0.5, 10.0, 0.5);
+fn spawn_level_targets(
+    mut commands: Commands,
+    mut events: EventReader<LevelLoaded>,
+    levels: Res<Assets<LevelDef>>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for ev in events.read() {
+        let Some(level) = levels.get(&ev.handle) else { continue; };
+        for (id, placement) in level.targets.iter().enumerate() {
+            spawn_target(
+                &mut commands,
+                &asset_server,
+                &mut meshes,
+                &mut materials,
+                id as u32,
+                0,
+                placement,
+            );
+        }
+    }
+}
 
-fn spawn_target(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// Spawns the target of `placement`, with the movement and armour of its
+/// kind.
+pub fn spawn_target(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    id: u32,
+    generation: u32,
+    placement: &TargetPlacement,
+) {
     let scene: Handle<Scene> = asset_server.load("models/targets.glb#Scene0");
-    commands
-        .spawn(SceneRoot(scene))
-        .insert(Transform::from_xyz(0.0, 0.0, 5.0))
+    let transform = placement.transform();
+    let moving = matches!(placement.kind, TargetKind::Patrol { .. } | TargetKind::PopUp { .. });
+    let mut entity = commands.spawn(SceneRoot(scene));
+    entity
+        .insert(transform)
         .insert(GlobalTransform::default())
         .insert(ColliderConstructorHierarchy::new(
             ColliderConstructor::TrimeshFromMesh,
         ))
-        .insert(RigidBody::Static)
-        .insert(Target::new(100, TARGET_HALF_EXTENTS));
-    info!("spawned target with hp 100");
+        .insert(if moving { RigidBody::Kinematic } else { RigidBody::Static })
+        .insert(Target {
+            id,
+            generation,
+            placement: placement.clone(),
+        })
+        .insert(Health::new(placement.hp as f32))
+        .insert(Damageable)
+        .insert(LevelEntity);
+    match &placement.kind {
+        TargetKind::Static => {}
+        TargetKind::Patrol { path, speed } => {
+            entity.insert(Patrol {
+                path: path
+                    .iter()
+                    .map(|p| transform.transform_point(Vec3::from_array(*p)))
+                    .collect(),
+                speed: *speed,
+                next: 0,
+            });
+        }
+        TargetKind::PopUp { up, down } => {
+            entity.insert(PopUp::new(*up, *down, transform.translation.y));
+        }
+        TargetKind::Armoured { armour, weak_points } => {
+            entity.insert(HitZone { multiplier: *armour });
+            let material = materials.add(StandardMaterial {
+                base_color: Color::srgb(1.0, 0.1, 0.1),
+                emissive: LinearRgba::from(Color::srgb(2.0, 0.0, 0.0)),
+                ..default()
+            });
+            entity.with_children(|target| {
+                for weak_point in weak_points {
+                    target
+                        .spawn(Mesh3d(meshes.add(Sphere::new(weak_point.radius))))
+                        .insert(MeshMaterial3d(material.clone()))
+                        .insert(Transform::from_translation(Vec3::from_array(weak_point.position)))
+                        .insert(Collider::sphere(weak_point.radius))
+                        .insert(HitZone {
+                            multiplier: weak_point.multiplier,
+                        });
+                }
+            });
+        }
+    }
+    info!("spawned target with hp {}", placement.hp);
+}
+
+fn patrol_targets(time: Res<Time>, mut targets: Query<(&mut Transform, &mut Patrol)>) {
+    for (mut tf, mut patrol) in &mut targets {
+        let Some(&goal) = patrol.path.get(patrol.next) else { continue; };
+        let to_goal = goal - tf.translation;
+        let step = patrol.speed * time.delta_secs();
+        if to_goal.length() <= step.max(PATROL_REACHED) {
+            tf.translation = goal;
+            patrol.next = (patrol.next + 1) % patrol.path.len();
+        } else {
+            tf.translation += to_goal.normalize() * step;
+        }
+    }
 }
 
-const LASER_DAMAGE: i32 = 5;
+fn pop_up_targets(time: Res<Time>, mut targets: Query<(&mut Transform, &mut PopUp)>) {
+    let dt = time.delta_secs();
+    for (mut tf, mut pop_up) in &mut targets {
+        let height = pop_up.update(dt);
+        let step = POP_UP_SPEED * dt;
+        tf.translation.y += (height - tf.translation.y).clamp(-step, step);
+    }
+}
 
-fn laser_hit_system(
+/// Despawns destroyed targets, leaving a [`TargetRespawn`] behind for those
+/// that come back.
+pub fn destroy_targets(
     mut commands: Commands,
-    mut lasers: Query<(&mut Transform, &mut Laser), Without<Target>>,
-    mut targets: Query<(Entity, &Transform, &mut Target), Without<Laser>>,
-    asset_server: Res<AssetServer>,
+    mut events: EventReader<DestroyedEvent>,
+    targets: Query<&Target>,
 ) {
-    for (mut laser_tf, mut laser) in &mut lasers {
-        for (target_entity, target_tf, mut target) in &mut targets {
-            if target.hp <= 0 {
-                continue;
-            }
-            info!(
-                "checking laser at {:?} against target at {:?}",
-                laser_tf.translation, target_tf.translation
-            );
-            let start = laser.prev_position;
-            let end = laser.projected_position;
-            if let Some(hit_pos) = segment_intersects_aabb(
-                start,
-                end,
-                target_tf.translation,
-                target.half_extents,
-            ) {
-                let normal = (hit_pos - target_tf.translation).normalize_or_zero();
-                let new_hp = (target.hp - LASER_DAMAGE).max(0);
-                target.hp = new_hp;
-                if new_hp == 0 {
-                    info!("despawning target {:?}", target_entity);
-                    commands.entity(target_entity).despawn();
-                }
-                info!("hit target {:?}, new hp {}", target_entity, new_hp);
-
-                let font: Handle<Font> = asset_server.load("fonts/Arial.ttf");
-                let text_pos = hit_pos + normal * 0.1;
-                commands.spawn((
-                    Text2d::new(format!("{} HP", new_hp)),
-                    TextFont {
-                        font,
-                        font_size: 20.0,
-                        ..default()
-                    },
-                    TextColor::WHITE,
-                    TextLayout::default(),
-                    Transform::from_translation(text_pos),
-                    HpText::new(1.0),
-                ));
-                info!("spawned hp text at {:?}", text_pos);
-
-                laser.velocity =
-                    (laser.velocity - 2.0 * laser.velocity.dot(normal) * normal)
-                        * crate::weapons::LASER_BOUNCE_DECAY;
-                laser_tf.translation = hit_pos;
-                break;
-            }
+    for event in events.read() {
+        let Ok(target) = targets.get(event.target) else { continue; };
+        info!("despawning target {:?}", event.target);
+        commands.entity(event.target).despawn();
+        if let Some(delay) = target.placement.respawn {
+            commands
+                .spawn(TargetRespawn {
+                    id: target.id,
+                    generation: target.generation,
+                    placement: target.placement.clone(),
+                    timer: Timer::from_seconds(delay, TimerMode::Once),
+                })
+                .insert(LevelEntity);
         }
-        laser.prev_position = laser_tf.translation;
     }
 }
 
-fn segment_intersects_aabb(
-    start: Vec3,
-    end: Vec3,
-    center: Vec3,
-    half_extents: Vec3,
-) -> Option<Vec3> {
-    let dir = end - start;
-    let axes = [
-        (start.x, dir.x, center.x - half_extents.x, center.x + half_extents.x),
-        (start.y, dir.y, center.y - half_extents.y, center.y + half_extents.y),
-        (start.z, dir.z, center.z - half_extents.z, center.z + half_extents.z),
-    ];
-    let mut tmin: f32 = 0.0;
-    let mut tmax: f32 = 1.0;
-    for (s, d, min, max) in axes {
-        if d.abs() < f32::EPSILON {
-            if s < min || s > max {
-                return None;
-            }
-        } else {
-            let inv_d = 1.0 / d;
-            let mut t1 = (min - s) * inv_d;
-            let mut t2 = (max - s) * inv_d;
-            if t1 > t2 {
-                std::mem::swap(&mut t1, &mut t2);
-            }
-            tmin = tmin.max(t1);
-            tmax = tmax.min(t2);
-            if tmin > tmax {
-                return None;
-            }
+fn respawn_targets(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut respawns: Query<(Entity, &mut TargetRespawn)>,
+) {
+    for (entity, mut respawn) in &mut respawns {
+        if !respawn.timer.tick(time.delta()).finished() {
+            continue;
         }
+        spawn_target(
+            &mut commands,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            respawn.id,
+            respawn.generation + 1,
+            &respawn.placement,
+        );
+        commands.entity(entity).despawn();
     }
-    Some(start + dir * tmin)
 }
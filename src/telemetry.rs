@@ -0,0 +1,280 @@
+use avian3d::prelude::{AngularVelocity, LinearVelocity};
+use bevy::prelude::*;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::drivetrain::Drivetrain;
+use crate::game_state::GameState;
+use crate::globals::Controlled;
+use crate::vehicle::Vehicle;
+use crate::vehicle_systems::{compute_tire_forces, RaycastWheel};
+
+/// Directory telemetry is exported to from the debug UI.
+pub const TELEMETRY_DIR: &str = "telemetry";
+/// Samples kept by default: 30 s at 60 ticks per second.
+const DEFAULT_CAPACITY: usize = 1800;
+
+/// State of one wheel at a physics tick.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct WheelSample {
+    pub is_front: bool,
+    pub is_left: bool,
+    pub compression: f32,
+    pub grounded: bool,
+    pub contact_normal: [f32; 3],
+    /// Normal load (N).
+    pub load: f32,
+    pub slip_ratio: f32,
+    /// Slip angle (rad).
+    pub slip_angle: f32,
+    /// Tire force along the wheel heading (N).
+    pub longitudinal_force: f32,
+    /// Tire force along the wheel side axis (N).
+    pub lateral_force: f32,
+    /// Spin rate (rad/s).
+    pub angular_velocity: f32,
+}
+
+/// State of the controlled vehicle at a physics tick.
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct TelemetrySample {
+    /// Seconds of simulated time.
+    pub time: f32,
+    /// Forward speed (m/s).
+    pub speed: f32,
+    pub linear_velocity: [f32; 3],
+    pub angular_velocity: [f32; 3],
+    pub throttle: f32,
+    pub brake: f32,
+    pub handbrake: f32,
+    pub steer: f32,
+    pub gear: i32,
+    pub rpm: f32,
+    pub wheels: Vec<WheelSample>,
+}
+
+/// Reasons telemetry could not be exported.
+#[derive(Debug)]
+pub enum TelemetryError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for TelemetryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryError::Io(e) => write!(f, "{e}"),
+            TelemetryError::Json(e) => write!(f, "could not serialise telemetry: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for TelemetryError {}
+
+impl From<std::io::Error> for TelemetryError {
+    fn from(e: std::io::Error) -> Self {
+        TelemetryError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TelemetryError {
+    fn from(e: serde_json::Error) -> Self {
+        TelemetryError::Json(e)
+    }
+}
+
+/// Ring buffer of the latest samples of the vehicle the player controls,
+/// for the debug UI plots and offline analysis. Other vehicles, including
+/// remote players', are never recorded.
+#[derive(Resource)]
+pub struct Telemetry {
+    /// Off until switched on from the debug UI.
+    pub recording: bool,
+    /// Oldest samples are dropped beyond this many.
+    pub capacity: usize,
+    vehicle: Option<Entity>,
+    samples: VecDeque<TelemetrySample>,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl Telemetry {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            recording: false,
+            capacity,
+            vehicle: None,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Adds a sample of `vehicle`. Switching to another vehicle starts a
+    /// new recording.
+    pub fn record(&mut self, vehicle: Entity, sample: TelemetrySample) {
+        if self.vehicle != Some(vehicle) {
+            self.samples.clear();
+            self.vehicle = Some(vehicle);
+        }
+        while self.samples.len() >= self.capacity.max(1) {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn samples(&self) -> &VecDeque<TelemetrySample> {
+        &self.samples
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// One row per sample, one column per value. Wheel columns are prefixed
+    /// with the wheel's index, e.g. `w0_compression`.
+    pub fn to_csv(&self) -> String {
+        let wheels = self.samples.iter().map(|s| s.wheels.len()).max().unwrap_or(0);
+        let mut csv = String::from(
+            "time,speed,vel_x,vel_y,vel_z,ang_vel_x,ang_vel_y,ang_vel_z,throttle,brake,handbrake,steer,gear,rpm",
+        );
+        for i in 0..wheels {
+            let _ = write!(
+                csv,
+                ",w{i}_compression,w{i}_grounded,w{i}_normal_x,w{i}_normal_y,w{i}_normal_z,w{i}_load,\
+                 w{i}_slip_ratio,w{i}_slip_angle,w{i}_longitudinal_force,w{i}_lateral_force,w{i}_angular_velocity"
+            );
+        }
+        csv.push('\n');
+        for s in &self.samples {
+            let [vx, vy, vz] = s.linear_velocity;
+            let [ax, ay, az] = s.angular_velocity;
+            let _ = write!(
+                csv,
+                "{},{},{vx},{vy},{vz},{ax},{ay},{az},{},{},{},{},{},{}",
+                s.time, s.speed, s.throttle, s.brake, s.handbrake, s.steer, s.gear, s.rpm
+            );
+            for w in &s.wheels {
+                let [nx, ny, nz] = w.contact_normal;
+                let _ = write!(
+                    csv,
+                    ",{},{},{nx},{ny},{nz},{},{},{},{},{},{}",
+                    w.compression,
+                    u8::from(w.grounded),
+                    w.load,
+                    w.slip_ratio,
+                    w.slip_angle,
+                    w.longitudinal_force,
+                    w.lateral_force,
+                    w.angular_velocity
+                );
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Result<String, TelemetryError> {
+        Ok(serde_json::to_string_pretty(&self.samples)?)
+    }
+
+    /// Writes the samples to `path` as JSON if it ends in `.json`, CSV
+    /// otherwise.
+    pub fn export(&self, path: &Path) -> Result<(), TelemetryError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = if path.extension().is_some_and(|e| e == "json") {
+            self.to_json()?
+        } else {
+            self.to_csv()
+        };
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// A fresh path under [`TELEMETRY_DIR`] with the given extension.
+pub fn export_path(extension: &str) -> PathBuf {
+    let stamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    Path::new(TELEMETRY_DIR).join(format!("telemetry-{stamp}.{extension}"))
+}
+
+/// Plugin recording the controlled vehicle's telemetry every physics tick
+/// while [`Telemetry::recording`] is on.
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Telemetry>().add_systems(
+            FixedUpdate,
+            record_telemetry
+                .after(compute_tire_forces)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+type RecordedVehicle = (
+    Entity,
+    &'static Vehicle,
+    &'static LinearVelocity,
+    &'static AngularVelocity,
+    Option<&'static Drivetrain>,
+    &'static Children,
+);
+
+fn record_telemetry(
+    time: Res<Time>,
+    mut telemetry: ResMut<Telemetry>,
+    vehicles: Query<RecordedVehicle, With<Controlled>>,
+    wheels: Query<&RaycastWheel>,
+) {
+    if !telemetry.recording {
+        return;
+    }
+    let Ok((entity, vehicle, lin_vel, ang_vel, drivetrain, children)) = vehicles.single() else {
+        return;
+    };
+    let wheels = children
+        .iter()
+        .filter_map(|child| wheels.get(child).ok())
+        .map(|wheel| WheelSample {
+            is_front: wheel.is_front,
+            is_left: wheel.is_left,
+            compression: wheel.compression,
+            grounded: wheel.grounded,
+            contact_normal: wheel.contact_normal.to_array(),
+            load: wheel.load,
+            slip_ratio: wheel.slip_ratio,
+            slip_angle: wheel.slip_angle,
+            longitudinal_force: wheel.tire_force.longitudinal,
+            lateral_force: wheel.tire_force.lateral,
+            angular_velocity: wheel.angular_velocity,
+        })
+        .collect();
+    telemetry.record(
+        entity,
+        TelemetrySample {
+            time: time.elapsed_secs(),
+            speed: vehicle.speed,
+            linear_velocity: lin_vel.0.to_array(),
+            angular_velocity: ang_vel.0.to_array(),
+            throttle: vehicle.throttle,
+            brake: vehicle.brake,
+            handbrake: vehicle.handbrake,
+            steer: vehicle.steer,
+            gear: drivetrain.map_or(0, |d| d.gear),
+            rpm: drivetrain.map_or(0.0, |d| d.rpm),
+            wheels,
+        },
+    );
+}
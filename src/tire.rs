@@ -0,0 +1,84 @@
+//! Tire model based on the Pacejka magic formula.
+//!
+//! Forces are computed in the wheel's contact frame: longitudinal force from
+//! the slip ratio between wheel and road speed, lateral force from the slip
+//! angle between the wheel heading and its direction of travel. The peak of
+//! each curve is `mu * load`, and a friction circle limits the combined force.
+
+/// Speed (m/s) below which slip is measured against this floor instead of
+/// the actual speed, so slip stays bounded when the car is nearly stopped.
+pub const MIN_SLIP_SPEED: f32 = 1.0;
+
+/// Shape of a magic formula curve `D * sin(C * atan(B*x - E*(B*x - atan(B*x))))`.
+/// The peak `D` is supplied per call as `mu * load`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MagicFormula {
+    /// Stiffness factor.
+    pub b: f32,
+    /// Shape factor.
+    pub c: f32,
+    /// Curvature factor.
+    pub e: f32,
+}
+
+impl MagicFormula {
+    /// Dry asphalt, peaking around 10% slip ratio.
+    pub const LONGITUDINAL: Self = Self {
+        b: 10.0,
+        c: 1.9,
+        e: 0.97,
+    };
+    /// Dry asphalt, peaking around 0.15 rad slip angle.
+    pub const LATERAL: Self = Self {
+        b: 10.0,
+        c: 1.3,
+        e: 0.97,
+    };
+
+    /// Force for `slip` with a peak of `peak`, signed like `slip`.
+    pub fn force(&self, slip: f32, peak: f32) -> f32 {
+        let bx = self.b * slip;
+        peak * (self.c * (bx - self.e * (bx - bx.atan())).atan()).sin()
+    }
+}
+
+/// Slip ratio of a wheel whose rim moves at `wheel_speed` (angular velocity
+/// times radius) over ground passing at `ground_speed`, both along the wheel
+/// heading. 0 is free rolling, -1 a locked wheel, positive is wheelspin.
+pub fn slip_ratio(wheel_speed: f32, ground_speed: f32) -> f32 {
+    let reference = ground_speed.abs().max(wheel_speed.abs()).max(MIN_SLIP_SPEED);
+    (wheel_speed - ground_speed) / reference
+}
+
+/// Slip angle (rad) between the wheel heading and its velocity at the
+/// contact, positive when the contact slides towards the wheel's side axis.
+pub fn slip_angle(long_speed: f32, lat_speed: f32) -> f32 {
+    lat_speed.atan2(long_speed.abs().max(MIN_SLIP_SPEED))
+}
+
+/// Contact forces of one tire.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TireForce {
+    /// Along the wheel heading (N).
+    pub longitudinal: f32,
+    /// Along the wheel side axis (N).
+    pub lateral: f32,
+}
+
+/// Longitudinal and lateral force of a tire under `load` newtons, with the
+/// combined force kept inside the friction ellipse of `mu_long`/`mu_lat`.
+pub fn tire_force(slip_ratio: f32, slip_angle: f32, load: f32, mu_long: f32, mu_lat: f32) -> TireForce {
+    let max_long = mu_long * load;
+    let max_lat = mu_lat * load;
+    let longitudinal = MagicFormula::LONGITUDINAL.force(slip_ratio, max_long);
+    let lateral = -MagicFormula::LATERAL.force(slip_angle, max_lat);
+
+    let usage = ((longitudinal / max_long.max(f32::EPSILON)).powi(2)
+        + (lateral / max_lat.max(f32::EPSILON)).powi(2))
+    .sqrt();
+    let scale = if usage > 1.0 { 1.0 / usage } else { 1.0 };
+    TireForce {
+        longitudinal: longitudinal * scale,
+        lateral: lateral * scale,
+    }
+}
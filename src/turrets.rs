@@ -0,0 +1,221 @@
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::actions::{Action, ActionState, PointerOverUi};
+use crate::game_state::GameState;
+use crate::globals::Controlled;
+use crate::vehicle_def::HardpointDef;
+use crate::weapon_def::WeaponDef;
+use crate::weapons::{fire_weapons, FireEvent};
+
+/// Aim turned per pixel of mouse motion (rad).
+const MOUSE_AIM_SENSITIVITY: f32 = 0.003;
+/// Aim turned per second with the stick or keys fully deflected (rad/s).
+const STICK_AIM_RATE: f32 = 2.0;
+/// Aim can't go further above or below the horizon than this (rad).
+const MAX_AIM_PITCH: f32 = FRAC_PI_2 * 0.9;
+/// Shots leave the turret this far (m) in front of its pivot.
+const MUZZLE_LENGTH: f32 = 1.0;
+
+/// Plugin aiming and firing the turrets of the driven vehicle.
+pub struct TurretPlugin;
+
+impl Plugin for TurretPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, aim_input.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                FixedUpdate,
+                (aim_turrets, turret_fire_system)
+                    .chain()
+                    .before(fire_weapons)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Where the driver of a vehicle with turrets is aiming, in world space, so
+/// the aim holds while the chassis turns under it.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct TurretAim {
+    /// Heading about +Y (rad); 0 looks along +Z.
+    pub yaw: f32,
+    /// Elevation above the horizon (rad).
+    pub pitch: f32,
+}
+
+impl TurretAim {
+    pub fn direction(&self) -> Vec3 {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(-self.pitch) * Vec3::Z
+    }
+
+    /// Yaw and pitch of the aim as seen from a chassis with `rotation`.
+    pub fn relative_to(&self, rotation: Quat) -> (f32, f32) {
+        let local = rotation.inverse() * self.direction();
+        (local.x.atan2(local.z), local.y.clamp(-1.0, 1.0).asin())
+    }
+}
+
+/// A weapon on a vehicle hardpoint. Its yaw and pitch are relative to the
+/// chassis.
+#[derive(Component, Clone, Debug)]
+pub struct Turret {
+    pub weapon: Handle<WeaponDef>,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// How far it turns either side of straight ahead (rad).
+    pub yaw_limit: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    /// Turn speed (rad/s).
+    pub turn_rate: f32,
+    /// Shortest time between shots (s).
+    pub cooldown: f32,
+    pub fire_timer: f32,
+}
+
+impl Turret {
+    pub fn new(def: &HardpointDef, weapon: Handle<WeaponDef>) -> Self {
+        let [min_pitch, max_pitch] = def.pitch_limits;
+        Self {
+            weapon,
+            yaw: 0.0,
+            pitch: 0.0,
+            yaw_limit: def.yaw_limit,
+            min_pitch,
+            max_pitch,
+            turn_rate: def.turn_rate,
+            cooldown: def.cooldown,
+            fire_timer: 0.0,
+        }
+    }
+
+    /// Turns towards `yaw` and `pitch` (chassis space) by at most
+    /// `turn_rate * dt`, stopping at the limits. Turrets that turn all the way
+    /// round take the short way; limited ones never swing through the back.
+    pub fn track(&mut self, yaw: f32, pitch: f32, dt: f32) {
+        let step = self.turn_rate * dt;
+        let yaw_delta = if self.yaw_limit >= PI {
+            wrap_angle(yaw - self.yaw)
+        } else {
+            wrap_angle(yaw).clamp(-self.yaw_limit, self.yaw_limit) - self.yaw
+        };
+        self.yaw = wrap_angle(self.yaw + yaw_delta.clamp(-step, step));
+        let pitch = pitch.clamp(self.min_pitch, self.max_pitch);
+        self.pitch += (pitch - self.pitch).clamp(-step, step);
+    }
+
+    /// Seconds between shots of `weapon`: its fire rate, held back by the
+    /// cooldown.
+    pub fn fire_interval(&self, weapon: &WeaponDef) -> f32 {
+        (1.0 / weapon.fire_rate.max(f32::EPSILON)).max(self.cooldown)
+    }
+
+    /// Rotation of the turret relative to the chassis.
+    pub fn rotation(&self) -> Quat {
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(-self.pitch)
+    }
+}
+
+/// `angle` wrapped into -pi..=pi.
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// Spawns the turret of a hardpoint as a child of the chassis, with a
+/// barrel pointing along its +Z.
+pub fn spawn_turret(
+    parent: &mut ChildSpawnerCommands,
+    def: &HardpointDef,
+    weapon: Handle<WeaponDef>,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+) {
+    parent
+        .spawn(Mesh3d(meshes.add(Cuboid::new(0.4, 0.2, 0.4))))
+        .insert(MeshMaterial3d(material.clone()))
+        .insert(Transform::from_translation(Vec3::from_array(def.position)))
+        .insert(Turret::new(def, weapon))
+        .with_children(|turret| {
+            turret
+                .spawn(Mesh3d(meshes.add(Cuboid::new(0.1, 0.1, MUZZLE_LENGTH))))
+                .insert(MeshMaterial3d(material))
+                .insert(Transform::from_xyz(0.0, 0.0, MUZZLE_LENGTH * 0.5));
+        });
+}
+
+/// Mouse motion and the aim axes turn the driven vehicle's aim. Runs every
+/// frame so no mouse motion is lost between physics ticks. Moving the mouse
+/// over a UI window doesn't aim.
+fn aim_input(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mouse: Option<Res<AccumulatedMouseMotion>>,
+    pointer_over_ui: Res<PointerOverUi>,
+    mut aims: Query<&mut TurretAim, With<Controlled>>,
+) {
+    let mouse = mouse
+        .filter(|_| !pointer_over_ui.0)
+        .map_or(Vec2::ZERO, |m| m.delta);
+    let rate = STICK_AIM_RATE * time.delta_secs();
+    for mut aim in &mut aims {
+        aim.yaw = wrap_angle(
+            aim.yaw - mouse.x * MOUSE_AIM_SENSITIVITY - actions.value(Action::AimHorizontal) * rate,
+        );
+        aim.pitch = (aim.pitch - mouse.y * MOUSE_AIM_SENSITIVITY
+            + actions.value(Action::AimVertical) * rate)
+            .clamp(-MAX_AIM_PITCH, MAX_AIM_PITCH);
+    }
+}
+
+fn aim_turrets(
+    time: Res<Time>,
+    vehicles: Query<(&Transform, &TurretAim, &Children)>,
+    mut turrets: Query<(&mut Transform, &mut Turret), Without<TurretAim>>,
+) {
+    let dt = time.delta_secs();
+    for (chassis_tf, aim, children) in &vehicles {
+        let (yaw, pitch) = aim.relative_to(chassis_tf.rotation);
+        for child in children.iter() {
+            let Ok((mut tf, mut turret)) = turrets.get_mut(child) else { continue; };
+            turret.track(yaw, pitch, dt);
+            tf.rotation = turret.rotation();
+        }
+    }
+}
+
+/// Fires the turrets of the driven vehicle while Fire is held. Vehicle
+/// weapons run off the engine, so instead of energy they wait out their
+/// cooldown.
+fn turret_fire_system(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    weapons: Res<Assets<WeaponDef>>,
+    vehicles: Query<(Entity, &Transform, &Children, Has<Controlled>), With<TurretAim>>,
+    mut turrets: Query<(&Transform, &mut Turret), Without<TurretAim>>,
+    mut shots: EventWriter<FireEvent>,
+) {
+    let dt = time.delta_secs();
+    for (vehicle, chassis_tf, children, controlled) in &vehicles {
+        for child in children.iter() {
+            let Ok((tf, mut turret)) = turrets.get_mut(child) else { continue; };
+            if turret.fire_timer > 0.0 {
+                turret.fire_timer -= dt;
+            }
+            let Some(def) = weapons.get(&turret.weapon) else { continue; };
+            if !controlled || !actions.pressed(Action::Fire) || turret.fire_timer > 0.0 {
+                continue;
+            }
+            let muzzle = chassis_tf.mul_transform(*tf);
+            let forward = muzzle.rotation * Vec3::Z;
+            shots.write(FireEvent {
+                shooter: vehicle,
+                weapon: turret.weapon.clone(),
+                origin: muzzle.translation + forward * MUZZLE_LENGTH,
+                direction: forward,
+            });
+            turret.fire_timer = turret.fire_interval(def);
+        }
+    }
+}
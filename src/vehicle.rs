@@ -1,26 +1,79 @@
 use bevy::prelude::*;
-use avian3d::prelude::{ColliderConstructor, ColliderConstructorHierarchy, RigidBody, LinearVelocity, AngularVelocity};
+use avian3d::prelude::{ColliderConstructor, ColliderConstructorHierarchy, RigidBody, LinearVelocity, AngularVelocity, ExternalForce, Mass, CenterOfMass};
+use avian3d::prelude::{Collider, ColliderOf, ShapeCastConfig, SpatialQuery, SpatialQueryFilter};
+use bevy::asset::LoadState;
 use bevy::ecs::hierarchy::ChildSpawnerCommands;
 use bevy::math::primitives::Cylinder;
-use rand::Rng;
 
-use crate::globals::{GameParams, Controlled, InVehicle};
+use crate::actions::{Action, ActionState};
+use crate::brakes::apply_brakes;
+use crate::drift::DriftState;
+use crate::drivetrain::{update_drivetrain, Drivetrain, Gearbox};
+use crate::fixed_step::InterpolatedTransform;
+use crate::game_state::GameState;
+use crate::health::{DamageStages, Damageable, Health, RespawnPoint, Wrecked};
+use crate::globals::{Controlled, InVehicle};
 use crate::input::Player;
-use crate::vehicle_systems::SuspensionTuning;
+use crate::turrets::{spawn_turret, TurretAim};
+use crate::level::{process_level_requests, LevelDef, LevelEntity, LevelLoaded};
+use crate::vehicle_def::{VehicleDef, VehicleDefLoader, WheelDef};
+use crate::vehicle_systems::{compute_tire_forces, RaycastWheel, SuspensionTuning};
 
+/// Driver inputs and the resulting motion of a car. The chassis is moved by
+/// the physics engine; `speed` and `yaw` are read back from it each tick.
 #[derive(Component, Default)]
 pub struct Vehicle {
+    /// Forward speed along the chassis (m/s).
     pub speed: f32,
     pub yaw: f32,
+    /// -1..=1, negative drives backwards.
+    pub throttle: f32,
+    /// 0..=1
+    pub brake: f32,
+    /// 0..=1, locks the rear wheels.
+    pub handbrake: f32,
+    /// -1 (left) ..=1 (right)
+    pub steer: f32,
+}
+
+/// Seat and door anchors of a vehicle, relative to the chassis. Occupants
+/// are found through their [`InVehicle`].
+#[derive(Component, Clone, Debug)]
+pub struct Seats(pub Vec<Seat>);
+
+#[derive(Clone, Copy, Debug)]
+pub struct Seat {
+    pub position: Vec3,
+    pub door: Vec3,
+}
+
+impl Seats {
+    /// Spots to get out of `seat` at, best first: its own door, the other
+    /// doors from nearest to furthest, then the roof above it.
+    pub fn exit_candidates(&self, seat: usize) -> Vec<Vec3> {
+        let Some(own) = self.0.get(seat) else { return vec![Vec3::Y * ROOF_EXIT_HEIGHT]; };
+        let mut doors: Vec<Vec3> = self.0.iter().map(|s| s.door).collect();
+        doors.sort_by(|a, b| a.distance(own.door).total_cmp(&b.distance(own.door)));
+        doors.dedup();
+        doors.push(own.position + Vec3::Y * ROOF_EXIT_HEIGHT);
+        doors
+    }
+}
+
+/// An occupant getting into or out of their seat, moving between two points
+/// in chassis space.
+#[derive(Component, Debug)]
+pub struct SeatAnimation {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub elapsed: f32,
+    pub exiting: bool,
 }
 
 #[derive(Component)]
 pub struct Wheel {
     pub is_front: bool,
     pub radius: f32,
-    pub rest_offset: Vec3,
-    pub suspension: f32,
-    pub phase: f32,
     pub rotation: f32,
 }
 
@@ -28,91 +81,194 @@ pub struct VehiclePlugin;
 
 impl Plugin for VehiclePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_vehicle)
+        app.init_asset::<VehicleDef>()
+            .init_asset_loader::<VehicleDefLoader>()
             .add_systems(
                 Update,
                 (
-                    vehicle_toggle_system,
-                    vehicle_input_system,
-                    vehicle_move_system.after(vehicle_input_system),
-                    wheel_update_system.after(vehicle_move_system),
-                    sync_player_to_vehicle_system,
+                    spawn_level_vehicles.after(process_level_requests),
+                    build_requested_vehicles.after(spawn_level_vehicles),
                 ),
+            )
+            // Entering and exiting reacts to a single press, so it stays in
+            // `Update` where `just_pressed` is seen exactly once.
+            .add_systems(Update, vehicle_toggle_system.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                FixedUpdate,
+                (
+                    vehicle_input_system
+                        .before(update_drivetrain)
+                        .before(apply_brakes),
+                    vehicle_motion_system.before(vehicle_input_system),
+                    wheel_update_system.after(compute_tire_forces),
+                    seat_occupants_system,
+                    eject_from_wrecks.before(seat_occupants_system),
+                )
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
 
-const WHEEL_RADIUS: f32 = 0.5;
-const WHEEL_WIDTH: f32 = 0.3;
-const FRONT_AXLE_Z: f32 = 1.5;
-const REAR_AXLE_Z: f32 = -1.5;
-const AXLE_X: f32 = 1.0;
-const SUSPENSION_TRAVEL: f32 = 0.2;
-const ENTER_DISTANCE: f32 = 2.0;
+/// How close (m) the player has to be to a door to get in.
+const ENTER_DISTANCE: f32 = 1.5;
+/// Seconds it takes to get in or out of a seat.
+const SEAT_ANIMATION_TIME: f32 = 0.4;
+/// The seat whose occupant drives.
+pub const DRIVER_SEAT: usize = 0;
+/// Height (m) above a seat where occupants get out when every door is blocked.
+const ROOF_EXIT_HEIGHT: f32 = 1.5;
+/// Below this speed (m/s) holding brake reverses instead.
+const REVERSE_SPEED: f32 = 1.0;
 
-fn spawn_vehicle(
+/// A vehicle waiting for its definition to load. The entity is turned into
+/// a car by [`build_requested_vehicles`] once the asset is ready.
+#[derive(Component)]
+pub struct VehicleRequest(pub Handle<VehicleDef>);
+
+fn spawn_level_vehicles(
     mut commands: Commands,
+    mut events: EventReader<LevelLoaded>,
+    levels: Res<Assets<LevelDef>>,
+    asset_server: Res<AssetServer>,
+) {
+    for ev in events.read() {
+        let Some(level) = levels.get(&ev.handle) else { continue; };
+        for placement in &level.vehicles {
+            let vehicle = spawn_vehicle(
+                &mut commands,
+                &asset_server,
+                &placement.vehicle,
+                placement.transform(),
+            );
+            commands.entity(vehicle).insert(LevelEntity);
+        }
+    }
+}
+
+/// Spawns the vehicle described by the `.vehicle.ron` asset at `path`. The
+/// returned entity gets its body and wheels once the definition has loaded.
+pub fn spawn_vehicle(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    path: &str,
+    transform: Transform,
+) -> Entity {
+    commands
+        .spawn(VehicleRequest(asset_server.load(path.to_string())))
+        .insert(transform)
+        .id()
+}
+
+/// Builds the body, physics and wheels of requested vehicles whose
+/// definitions have loaded.
+pub fn build_requested_vehicles(
+    mut commands: Commands,
+    requests: Query<(Entity, &VehicleRequest, &Transform)>,
+    defs: Res<Assets<VehicleDef>>,
+    asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_server: Res<AssetServer>,
-    mut tuning: ResMut<SuspensionTuning>,   // <-- add this
+    tuning: Res<SuspensionTuning>,
 ) {
-    // ----- mass-aware damping ---------------------------------------------
-    const CHASSIS_MASS: f32 = 800.0;        // same value you stick in Chassis
-    tuning.c = 2.0 * (tuning.k * (CHASSIS_MASS / 4.0)).sqrt();
-    // -----------------------------------------------------------------------
+    for (entity, request, transform) in &requests {
+        if let LoadState::Failed(err) = asset_server.load_state(&request.0) {
+            error!("failed to load vehicle: {err}");
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let Some(def) = defs.get(&request.0) else { continue; };
+        build_vehicle(
+            &mut commands,
+            entity,
+            def,
+            &asset_server,
+            &mut meshes,
+            &mut materials,
+            &tuning,
+            *transform,
+        );
+        info!("spawned vehicle '{}'", def.name);
+    }
+}
 
-    let scene: Handle<Scene> = asset_server.load("models/car.glb#Scene0");
-    let vehicle = commands
-        .spawn(SceneRoot(scene))
-        .insert(Transform::from_xyz(0.0, WHEEL_RADIUS + 0.5, 0.0))
-        .insert(GlobalTransform::default())
-        .insert(Vehicle::default())
+#[allow(clippy::too_many_arguments)]
+fn build_vehicle(
+    commands: &mut Commands,
+    vehicle: Entity,
+    def: &VehicleDef,
+    asset_server: &AssetServer,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    tuning: &SuspensionTuning,
+    transform: Transform,
+) {
+    let mut entity = commands.entity(vehicle);
+    entity
+        .remove::<VehicleRequest>()
+        .insert(SceneRoot(asset_server.load(def.model.clone())))
+        .insert(Vehicle {
+            yaw: transform.rotation.to_euler(EulerRot::YXZ).0,
+            ..default()
+        })
         .insert(RigidBody::Dynamic)
         .insert(ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh))
         .insert(LinearVelocity::ZERO)
         .insert(AngularVelocity::ZERO)
-        .insert(crate::vehicle_systems::Chassis { mass: CHASSIS_MASS })
-        .id();
+        .insert(Mass(def.mass))
+        .insert(ExternalForce::new(Vec3::ZERO).with_persistence(false))
+        .insert(crate::vehicle_systems::Chassis { mass: def.mass })
+        .insert(def.drivetrain.clone())
+        .insert(def.brakes)
+        .insert(DriftState::default())
+        .insert(Health::new(def.health))
+        .insert(DamageStages::new(def.damage_stages.clone()))
+        .insert(Damageable)
+        .insert(RespawnPoint(transform))
+        .insert(Seats(
+            def.seats
+                .iter()
+                .map(|seat| Seat {
+                    position: Vec3::from_array(seat.position),
+                    door: Vec3::from_array(seat.door),
+                })
+                .collect(),
+        ))
+        .insert(InterpolatedTransform::default());
+    if let Some(com) = def.center_of_mass {
+        entity.insert(CenterOfMass(Vec3::from_array(com)));
+    }
+    if !def.hardpoints.is_empty() {
+        entity.insert(TurretAim {
+            yaw: transform.rotation.to_euler(EulerRot::YXZ).0,
+            pitch: 0.0,
+        });
+    }
+    // Without its own tuning a vehicle copies the global one, critically
+    // damped for its mass.
+    let suspension = def.suspension.unwrap_or(SuspensionTuning {
+        c: 2.0 * (tuning.k * (def.mass / 4.0)).sqrt(),
+        ..*tuning
+    });
+    entity.insert(suspension);
 
-    let wheel_mesh = meshes.add(Mesh::from(Cylinder {
-        radius: WHEEL_RADIUS,
-        half_height: WHEEL_WIDTH * 0.5,
-    }));
     let wheel_material = materials.add(StandardMaterial {
         base_color: Color::srgb(0.1, 0.1, 0.1),
         ..default()
     });
-
-    commands.entity(vehicle).with_children(|p| {
-        spawn_wheel(
-            p,
-            wheel_mesh.clone(),
-            wheel_material.clone(),
-            Vec3::new(AXLE_X, -WHEEL_RADIUS, FRONT_AXLE_Z),
-            true,
-        );
-        spawn_wheel(
-            p,
-            wheel_mesh.clone(),
-            wheel_material.clone(),
-            Vec3::new(-AXLE_X, -WHEEL_RADIUS, FRONT_AXLE_Z),
-            true,
-        );
-        spawn_wheel(
-            p,
-            wheel_mesh.clone(),
-            wheel_material.clone(),
-            Vec3::new(AXLE_X, -WHEEL_RADIUS, REAR_AXLE_Z),
-            false,
-        );
-        spawn_wheel(
-            p,
-            wheel_mesh,
-            wheel_material,
-            Vec3::new(-AXLE_X, -WHEEL_RADIUS, REAR_AXLE_Z),
-            false,
-        );
+    let drivetrain = &def.drivetrain;
+    entity.with_children(|p| {
+        for wheel in &def.wheels {
+            let mesh = meshes.add(Mesh::from(Cylinder {
+                radius: wheel.radius,
+                half_height: wheel.width * 0.5,
+            }));
+            let driven = drivetrain.layout.axle_share(wheel.front, drivetrain.front_split) > 0.0;
+            spawn_wheel(p, mesh, wheel_material.clone(), wheel, driven);
+        }
+        for hardpoint in &def.hardpoints {
+            let weapon = asset_server.load(hardpoint.weapon.clone());
+            spawn_turret(p, hardpoint, weapon, meshes, wheel_material.clone());
+        }
     });
 }
 
@@ -120,144 +276,307 @@ fn spawn_wheel(
     parent: &mut ChildSpawnerCommands,
     mesh: Handle<Mesh>,
     material: Handle<StandardMaterial>,
-    offset: Vec3,
-    is_front: bool,
+    def: &WheelDef,
+    driven: bool,
 ) {
+    let offset = Vec3::from_array(def.position);
     parent
         .spawn(Mesh3d(mesh))
         .insert(MeshMaterial3d(material))
         .insert(Transform::from_translation(offset))
-        .insert(crate::vehicle_systems::RaycastWheel::new(
+        .insert(RaycastWheel::new(
             offset,
-            WHEEL_RADIUS,
-            is_front,
+            def.radius,
+            def.front,
             offset.x < 0.0,
+            driven,
         ))
         .insert(Wheel {
-            is_front,
-            radius: WHEEL_RADIUS,
-            rest_offset: offset,
-            suspension: SUSPENSION_TRAVEL,
-            phase: rand::thread_rng().gen::<f32>() * std::f32::consts::TAU,
+            is_front: def.front,
+            radius: def.radius,
             rotation: 0.0,
         });
 }
 
+/// A vehicle and whether it can be driven.
+type DriverInputs = (
+    &'static mut Vehicle,
+    Option<&'static Drivetrain>,
+    Has<Controlled>,
+    Has<Wrecked>,
+);
+
 fn vehicle_input_system(
-    time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>,
-    params: Res<GameParams>,
-    mut q: Query<&mut Vehicle, With<Controlled>>,
+    actions: Res<ActionState>,
+    mut q: Query<DriverInputs>,
 ) {
-    let dt = time.delta_secs();
-    for mut vehicle in &mut q {
-        if keys.pressed(KeyCode::KeyW) {
-            vehicle.speed = (vehicle.speed + params.acceleration * dt).min(params.max_speed);
-        } else if keys.pressed(KeyCode::KeyS) {
-            vehicle.speed = (vehicle.speed - params.brake_acceleration * dt).max(-params.max_speed);
-        } else {
-            vehicle.speed = vehicle.speed.signum()
-                * (vehicle.speed.abs() - params.friction * dt).max(0.0);
-        }
-
-        if keys.pressed(KeyCode::KeyA) {
-            vehicle.yaw += params.yaw_rate * dt;
+    for (mut vehicle, drivetrain, controlled, wrecked) in &mut q {
+        if !controlled || wrecked {
+            vehicle.throttle = 0.0;
+            vehicle.brake = 0.0;
+            vehicle.handbrake = 0.0;
+            vehicle.steer = 0.0;
+            continue;
         }
-        if keys.pressed(KeyCode::KeyD) {
-            vehicle.yaw -= params.yaw_rate * dt;
+        let accelerate = actions.value(Action::Accelerate);
+        let brake = actions.value(Action::Brake);
+        // A manual gearbox reverses by selecting reverse gear instead.
+        let automatic = drivetrain.is_none_or(|d| d.gearbox == Gearbox::Automatic);
+        if automatic && accelerate <= 0.0 && brake > 0.0 && vehicle.speed < REVERSE_SPEED {
+            vehicle.throttle = -brake;
+            vehicle.brake = 0.0;
+        } else {
+            vehicle.throttle = accelerate;
+            vehicle.brake = brake;
         }
+        vehicle.handbrake = actions.value(Action::Handbrake);
+        vehicle.steer = actions.value(Action::Steer);
     }
 }
 
-fn vehicle_move_system(
-    time: Res<Time>,
-    mut q: Query<(&mut Transform, &Vehicle), With<Controlled>>,
-) {
-    let dt = time.delta_secs();
-    for (mut tf, vehicle) in &mut q {
-        let yaw_rot = Quat::from_rotation_y(vehicle.yaw);
-        tf.rotation = yaw_rot;
-        let forward = yaw_rot * Vec3::Z;
-        tf.translation += forward * vehicle.speed * dt;
+/// Reads speed and heading back from the chassis body.
+fn vehicle_motion_system(mut q: Query<(&Transform, &LinearVelocity, &mut Vehicle)>) {
+    for (tf, lin_vel, mut vehicle) in &mut q {
+        let forward = tf.rotation * Vec3::Z;
+        vehicle.speed = lin_vel.0.dot(forward);
+        vehicle.yaw = tf.rotation.to_euler(EulerRot::YXZ).0;
     }
 }
 
 fn wheel_update_system(
     time: Res<Time>,
-    vehicles: Query<&Vehicle>,
-    mut wheels: Query<(&ChildOf, &mut Transform, &mut Wheel)>,
+    mut wheels: Query<(&mut Transform, &mut Wheel, &RaycastWheel)>,
 ) {
     let dt = time.delta_secs();
-    let elapsed = time.elapsed_secs();
-    for (parent, mut tf, mut wheel) in &mut wheels {
-        if let Ok(vehicle) = vehicles.get(parent.parent()) {
-            wheel.rotation += vehicle.speed * dt / wheel.radius;
-            let steer = if wheel.is_front { vehicle.yaw } else { 0.0 };
-            // keep wheel upright while allowing steering and rolling
-            tf.rotation =
-                Quat::from_rotation_y(steer)
-                    * Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)
-                    * Quat::from_rotation_x(wheel.rotation);
-            let y_off = (elapsed + wheel.phase).sin() * wheel.suspension;
-            tf.translation = wheel.rest_offset + Vec3::Y * y_off;
-        }
+    for (mut tf, mut wheel, raycast) in &mut wheels {
+        wheel.rotation += raycast.angular_velocity * dt;
+        // keep wheel upright while allowing steering and rolling
+        tf.rotation = Quat::from_rotation_y(raycast.steer_angle)
+            * Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)
+            * Quat::from_rotation_x(wheel.rotation);
     }
 }
 
+type EnteringPlayer = (
+    Entity,
+    &'static Transform,
+    &'static Player,
+    Option<&'static InVehicle>,
+    Has<Controlled>,
+);
+type EnterableVehicle = (
+    Entity,
+    &'static Transform,
+    &'static Seats,
+    Has<Controlled>,
+    Has<Wrecked>,
+);
+
+/// Enters the nearest free seat in reach, or leaves the current one.
 fn vehicle_toggle_system(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut commands: Commands,
-    mut players: Query<
-        (Entity, &mut Transform, Option<&InVehicle>, Option<&Controlled>),
-        (With<Player>, Without<Vehicle>),
-    >,
-    mut vehicles: Query<
-        (Entity, &Transform, Option<&Controlled>),
-        (With<Vehicle>, Without<Player>),
-    >,
+    spatial: SpatialQuery,
+    players: Query<EnteringPlayer, (Without<Vehicle>, Without<SeatAnimation>)>,
+    vehicles: Query<EnterableVehicle, (With<Vehicle>, Without<Player>)>,
+    occupants: Query<&InVehicle>,
+    colliders: Query<(Entity, &ColliderOf)>,
 ) {
-    if !keys.just_pressed(KeyCode::KeyE) {
+    if !actions.just_pressed(Action::EnterExit) {
         return;
     }
-    let (player_ent, mut player_tf, in_vehicle, player_ctrl) = match players.single_mut() {
-        Ok(v) => v,
-        Err(_) => return,
+    // The local player is the one being controlled, directly or through the
+    // vehicle they drive.
+    let Some((player_ent, player_tf, player, in_vehicle, _)) =
+        players.iter().find(|(_, _, _, in_vehicle, controlled)| {
+            *controlled
+                || in_vehicle.is_some_and(|occupy| {
+                    occupy.seat == DRIVER_SEAT
+                        && vehicles.get(occupy.vehicle).is_ok_and(|v| v.3)
+                })
+        })
+    else {
+        return;
     };
 
-    if player_ctrl.is_some() {
-        for (veh_ent, veh_tf, veh_ctrl) in &mut vehicles {
-            if veh_ctrl.is_some() {
-                continue;
-            }
-            if player_tf.translation.distance(veh_tf.translation) < ENTER_DISTANCE {
-                commands.entity(player_ent)
-                    .remove::<Controlled>()
-                    .insert(InVehicle { vehicle: veh_ent })
-                    .insert(Visibility::Hidden);
-                commands.entity(veh_ent).insert(Controlled);
-                player_tf.translation = veh_tf.translation;
-                break;
-            }
-        }
-    } else if let Some(occupy) = in_vehicle {
-        if let Ok((veh_ent, veh_tf, _)) = vehicles.get(occupy.vehicle) {
-            commands.entity(player_ent)
-                .insert(Controlled)
-                .remove::<InVehicle>()
-                .insert(Visibility::Visible);
-            commands.entity(veh_ent).remove::<Controlled>();
-            player_tf.translation = veh_tf.translation + Vec3::Y;
-        }
+    if let Some(occupy) = in_vehicle {
+        let Ok((veh_ent, veh_tf, seats, _, _)) = vehicles.get(occupy.vehicle) else { return; };
+        let exit = find_exit(&spatial, &colliders, player_ent, player, veh_ent, veh_tf, seats, occupy.seat);
+        begin_exit(&mut commands, player_ent, player_tf, occupy, veh_tf, exit);
+        return;
+    }
+
+    let nearest = vehicles
+        .iter()
+        .filter(|(_, _, _, _, wrecked)| !wrecked)
+        .flat_map(|(veh_ent, veh_tf, seats, _, _)| {
+            seats.0.iter().enumerate().map(move |(index, seat)| {
+                let door = veh_tf.transform_point(seat.door);
+                (veh_ent, veh_tf, index, seat.position, player_tf.translation.distance(door))
+            })
+        })
+        .filter(|&(veh_ent, _, index, _, distance)| {
+            distance < ENTER_DISTANCE
+                && !occupants.iter().any(|o| o.vehicle == veh_ent && o.seat == index)
+        })
+        .min_by(|a, b| a.4.total_cmp(&b.4));
+    let Some((veh_ent, veh_tf, seat, seat_position, _)) = nearest else { return; };
+    commands.entity(player_ent)
+        .insert(InVehicle { vehicle: veh_ent, seat })
+        .insert(SeatAnimation {
+            from: veh_tf.compute_affine().inverse().transform_point3(player_tf.translation),
+            to: seat_position,
+            elapsed: 0.0,
+            exiting: false,
+        });
+    if seat == DRIVER_SEAT {
+        commands.entity(player_ent).remove::<Controlled>();
+        commands.entity(veh_ent).insert(Controlled);
+    }
+}
+
+/// Finds where an occupant of `seat` can get out, relative to the chassis.
+/// Each of [`Seats::exit_candidates`] is tried in turn; a spot is free if the
+/// player's collider can move there from the seat without hitting anything
+/// but the vehicle itself. Falls back to the roof.
+#[allow(clippy::too_many_arguments)]
+fn find_exit(
+    spatial: &SpatialQuery,
+    colliders: &Query<(Entity, &ColliderOf)>,
+    player_ent: Entity,
+    player: &Player,
+    veh_ent: Entity,
+    veh_tf: &Transform,
+    seats: &Seats,
+    seat: usize,
+) -> Vec3 {
+    let shape = Collider::cuboid(player.half_extents.x, player.half_extents.y, player.half_extents.z);
+    let own = colliders
+        .iter()
+        .filter(|(_, collider_of)| collider_of.body == veh_ent)
+        .map(|(entity, _)| entity);
+    let filter = SpatialQueryFilter::from_excluded_entities(own.chain([veh_ent, player_ent]));
+    let from = veh_tf.transform_point(seats.0.get(seat).map_or(Vec3::ZERO, |s| s.position));
+    let candidates = seats.exit_candidates(seat);
+    let fallback = *candidates.last().unwrap();
+    candidates
+        .into_iter()
+        .find(|&candidate| {
+            let to = veh_tf.transform_point(candidate);
+            let path = to - from;
+            let blocked = Dir3::new(path).is_ok_and(|dir| {
+                spatial
+                    .cast_shape(
+                        &shape,
+                        from,
+                        Quat::IDENTITY,
+                        dir,
+                        &ShapeCastConfig {
+                            max_distance: path.length(),
+                            ..Default::default()
+                        },
+                        &filter,
+                    )
+                    .is_some()
+            });
+            !blocked && spatial.shape_intersections(&shape, to, Quat::IDENTITY, &filter).is_empty()
+        })
+        .unwrap_or(fallback)
+}
+
+/// Starts moving an occupant out to `exit` (chassis space). Control returns to
+/// them once they are out; the vehicle stops responding straight away.
+fn begin_exit(
+    commands: &mut Commands,
+    player_ent: Entity,
+    player_tf: &Transform,
+    occupy: &InVehicle,
+    veh_tf: &Transform,
+    exit: Vec3,
+) {
+    commands.entity(player_ent)
+        .insert(Visibility::Visible)
+        .insert(SeatAnimation {
+            from: veh_tf.compute_affine().inverse().transform_point3(player_tf.translation),
+            to: exit,
+            elapsed: 0.0,
+            exiting: true,
+        });
+    if occupy.seat == DRIVER_SEAT {
+        commands.entity(occupy.vehicle).remove::<Controlled>();
     }
 }
 
-fn sync_player_to_vehicle_system(
-    mut players: Query<(&mut Transform, &InVehicle), Without<Vehicle>>,
-    vehicles: Query<&Transform, (With<Vehicle>, Without<Player>)>,
+type Occupant = (
+    Entity,
+    &'static Transform,
+    &'static Player,
+    &'static InVehicle,
+    Option<&'static SeatAnimation>,
+);
+type NewWreck = (With<Vehicle>, Added<Wrecked>);
+
+/// Throws all occupants out of a vehicle as soon as it is wrecked.
+fn eject_from_wrecks(
+    mut commands: Commands,
+    spatial: SpatialQuery,
+    players: Query<Occupant, Without<Vehicle>>,
+    wrecks: Query<(Entity, &Transform, &Seats), NewWreck>,
+    colliders: Query<(Entity, &ColliderOf)>,
 ) {
-    for (mut tf, iv) in &mut players {
-        if let Ok(v_tf) = vehicles.get(iv.vehicle) {
-            tf.translation = v_tf.translation;
+    for (player_ent, player_tf, player, occupy, animation) in &players {
+        if animation.is_some_and(|a| a.exiting) {
+            continue;
         }
+        let Ok((veh_ent, veh_tf, seats)) = wrecks.get(occupy.vehicle) else { continue; };
+        let exit = find_exit(&spatial, &colliders, player_ent, player, veh_ent, veh_tf, seats, occupy.seat);
+        begin_exit(&mut commands, player_ent, player_tf, occupy, veh_tf, exit);
+    }
+}
+
+type SeatedOccupant = (
+    Entity,
+    &'static mut Transform,
+    &'static InVehicle,
+    Option<&'static mut SeatAnimation>,
+);
+
+/// Keeps occupants in their seats and plays getting in and out.
+fn seat_occupants_system(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut players: Query<SeatedOccupant, Without<Vehicle>>,
+    vehicles: Query<(&Transform, &Seats), With<Vehicle>>,
+) {
+    for (player_ent, mut tf, occupy, animation) in &mut players {
+        let Ok((veh_tf, seats)) = vehicles.get(occupy.vehicle) else {
+            // The vehicle is gone, e.g. with its level. Leave the occupant
+            // where its seat last was.
+            commands.entity(player_ent)
+                .remove::<(InVehicle, SeatAnimation)>()
+                .insert(Visibility::Inherited)
+                .insert(Controlled);
+            continue;
+        };
+        let seat = seats.0.get(occupy.seat).map_or(Vec3::ZERO, |s| s.position);
+        let local = match animation {
+            None => seat,
+            Some(mut animation) => {
+                animation.elapsed += time.delta_secs();
+                let t = (animation.elapsed / SEAT_ANIMATION_TIME).min(1.0);
+                if t >= 1.0 {
+                    let mut player = commands.entity(player_ent);
+                    player.remove::<SeatAnimation>();
+                    if animation.exiting {
+                        player.remove::<InVehicle>().insert(Controlled);
+                    } else {
+                        player.insert(Visibility::Hidden);
+                    }
+                }
+                let eased = t * t * (3.0 - 2.0 * t);
+                animation.from.lerp(animation.to, eased)
+            }
+        };
+        tf.translation = veh_tf.transform_point(local);
     }
 }
@@ -0,0 +1,173 @@
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+use crate::brakes::Brakes;
+use crate::drivetrain::Drivetrain;
+use crate::vehicle_systems::SuspensionTuning;
+
+/// Vehicle spawned when a level placement doesn't name one.
+pub const DEFAULT_VEHICLE: &str = "vehicles/car.vehicle.ron";
+
+/// A wheel of a vehicle definition.
+#[derive(Deserialize, Clone, Debug)]
+pub struct WheelDef {
+    /// Suspension mount relative to the chassis origin.
+    pub position: [f32; 3],
+    pub radius: f32,
+    #[serde(default = "default_wheel_width")]
+    pub width: f32,
+    /// Front wheels steer and form the front axle.
+    pub front: bool,
+}
+
+fn default_wheel_width() -> f32 {
+    0.3
+}
+
+/// A seat of a vehicle definition.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SeatDef {
+    /// Where the occupant sits, relative to the chassis origin.
+    pub position: [f32; 3],
+    /// Where the occupant gets in and out, relative to the chassis origin.
+    pub door: [f32; 3],
+}
+
+/// A weapon mount of a vehicle definition. The turret on it aims
+/// independently of the chassis, within its limits.
+#[derive(Deserialize, Clone, Debug)]
+pub struct HardpointDef {
+    /// Turret pivot relative to the chassis origin.
+    pub position: [f32; 3],
+    /// Path of the `.weapon.ron` it carries.
+    pub weapon: String,
+    /// How far the turret turns either side of straight ahead (rad). Pi or
+    /// more turns all the way round.
+    #[serde(default = "default_yaw_limit")]
+    pub yaw_limit: f32,
+    /// Lowest and highest elevation (rad).
+    #[serde(default = "default_pitch_limits")]
+    pub pitch_limits: [f32; 2],
+    /// Turn speed (rad/s).
+    #[serde(default = "default_turn_rate")]
+    pub turn_rate: f32,
+    /// Shortest time between shots (s). Vehicle weapons don't use the
+    /// driver's energy, so this is what holds back fast firing ones.
+    #[serde(default = "default_cooldown")]
+    pub cooldown: f32,
+}
+
+fn default_yaw_limit() -> f32 {
+    std::f32::consts::PI
+}
+
+fn default_pitch_limits() -> [f32; 2] {
+    [-0.2, 0.6]
+}
+
+fn default_turn_rate() -> f32 {
+    3.0
+}
+
+fn default_cooldown() -> f32 {
+    0.25
+}
+
+fn default_health() -> f32 {
+    200.0
+}
+
+fn default_damage_stages() -> Vec<f32> {
+    vec![0.66, 0.33]
+}
+
+fn default_seats() -> Vec<SeatDef> {
+    vec![SeatDef {
+        position: [0.0, 0.0, 0.0],
+        door: [-1.5, -0.5, 0.0],
+    }]
+}
+
+/// Description of a car, stored as a `.vehicle.ron` asset.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct VehicleDef {
+    pub name: String,
+    /// Scene path of the body, e.g. `models/car.glb#Scene0`.
+    pub model: String,
+    /// Chassis mass (kg).
+    pub mass: f32,
+    /// Hit points before the vehicle is wrecked.
+    #[serde(default = "default_health")]
+    pub health: f32,
+    /// Health fractions, highest first, at which the vehicle shows more
+    /// damage. See [`DamageStages`](crate::health::DamageStages).
+    #[serde(default = "default_damage_stages")]
+    pub damage_stages: Vec<f32>,
+    /// Centre of mass relative to the chassis origin. Computed from the
+    /// collider when omitted.
+    #[serde(default)]
+    pub center_of_mass: Option<[f32; 3]>,
+    pub wheels: Vec<WheelDef>,
+    /// The first seat is the driver's; the rest carry passengers.
+    #[serde(default = "default_seats")]
+    pub seats: Vec<SeatDef>,
+    /// Weapon mounts, fired by the driver.
+    #[serde(default)]
+    pub hardpoints: Vec<HardpointDef>,
+    /// Suspension and tire parameters. Vehicles without their own get a copy
+    /// of the global [`SuspensionTuning`] resource with the damping set
+    /// critical for their mass.
+    #[serde(default)]
+    pub suspension: Option<SuspensionTuning>,
+    /// Engine, gearbox and differentials.
+    #[serde(default)]
+    pub drivetrain: Drivetrain,
+    #[serde(default)]
+    pub brakes: Brakes,
+}
+
+#[derive(Debug)]
+pub enum VehicleDefLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for VehicleDefLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VehicleDefLoaderError::Io(e) => write!(f, "could not read vehicle: {e}"),
+            VehicleDefLoaderError::Ron(e) => write!(f, "could not parse vehicle: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for VehicleDefLoaderError {}
+
+#[derive(Default)]
+pub struct VehicleDefLoader;
+
+impl AssetLoader for VehicleDefLoader {
+    type Asset = VehicleDef;
+    type Settings = ();
+    type Error = VehicleDefLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(VehicleDefLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(VehicleDefLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vehicle.ron"]
+    }
+}
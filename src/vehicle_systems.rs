@@ -1,18 +1,29 @@
 use bevy::prelude::*;
 use avian3d::prelude::*;
+use serde::{Deserialize, Serialize};
 
-/// Tuning parameters for the vehicle suspension system.
-#[derive(Resource, Clone, Copy)]
+use crate::game_state::GameState;
+use crate::globals::GameParams;
+use crate::tire::{slip_angle, slip_ratio, tire_force, TireForce};
+use crate::vehicle::Vehicle;
+
+/// Mass of a wheel and tire (kg), used for the wheel's spin inertia.
+const WHEEL_MASS: f32 = 20.0;
+
+/// Tuning parameters for the vehicle suspension system. The resource applies
+/// to every vehicle without its own copy on the chassis.
+#[derive(Resource, Component, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SuspensionTuning {
     /// Spring stiffness coefficient (N/m).
     pub k: f32,
     /// Damping coefficient (N·s/m).
     pub c: f32,
-    /// Longitudinal tire coefficient.
+    /// Peak longitudinal tire friction coefficient.
     pub mu_long: f32,
-    /// Lateral tire coefficient.
+    /// Peak lateral tire friction coefficient.
     pub mu_lat: f32,
-    /// Anti-roll bar stiffness.
+    /// Anti-roll bar stiffness (N/m of compression difference across an axle).
     pub k_anti_roll: f32,
     /// Suspension rest length (m).
     pub rest_length: f32,
@@ -64,10 +75,36 @@ pub struct RaycastWheel {
     pub contact_normal: Vec3,
     /// Whether the wheel is in contact with the ground.
     pub grounded: bool,
+    /// Normal force pressing the tire into the ground this tick (N).
+    pub load: f32,
+    /// Spring and damper force this tick (N), before the anti-roll bar.
+    pub suspension_force: f32,
+    /// Steering angle around the chassis up axis (rad), positive turns left.
+    pub steer_angle: f32,
+    /// True if the engine drives this wheel.
+    pub driven: bool,
+    /// Torque from the drivetrain this tick (N·m), positive drives forwards.
+    pub drive_torque: f32,
+    /// Brake torque this tick (N·m), always opposing the spin.
+    pub brake_torque: f32,
+    /// Slip ratio at the last tire force update, -1 when locked.
+    pub slip_ratio: f32,
+    /// Slip angle at the last tire force update (rad).
+    pub slip_angle: f32,
+    /// Spin rate around the axle (rad/s), positive rolls forwards.
+    pub angular_velocity: f32,
+    /// Contact force at the last tire force update.
+    pub tire_force: TireForce,
 }
 
 impl RaycastWheel {
-    pub fn new(mount: Vec3, radius: f32, is_front: bool, is_left: bool) -> Self {
+    /// Moment of inertia around the axle (kg·m²), treating the wheel as a
+    /// solid disc.
+    pub fn inertia(&self) -> f32 {
+        0.5 * WHEEL_MASS * self.radius * self.radius
+    }
+
+    pub fn new(mount: Vec3, radius: f32, is_front: bool, is_left: bool, driven: bool) -> Self {
         Self {
             mount,
             radius,
@@ -78,6 +115,16 @@ impl RaycastWheel {
             contact_point: Vec3::ZERO,
             contact_normal: Vec3::Y,
             grounded: false,
+            load: 0.0,
+            suspension_force: 0.0,
+            steer_angle: 0.0,
+            driven,
+            drive_torque: 0.0,
+            brake_torque: 0.0,
+            slip_ratio: 0.0,
+            slip_angle: 0.0,
+            angular_velocity: 0.0,
+            tire_force: TireForce::default(),
         }
     }
 }
@@ -87,38 +134,48 @@ pub struct VehiclePhysicsPlugin;
 
 impl Plugin for VehiclePhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SuspensionTuning::default())
+        app.init_resource::<SuspensionTuning>()
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
                     raycast_wheels,
                     apply_suspension.after(raycast_wheels),
-                    compute_tire_forces.after(apply_suspension),
-                    apply_anti_roll.after(compute_tire_forces),
-                ),
+                    apply_anti_roll.after(apply_suspension),
+                    compute_tire_forces.after(apply_anti_roll),
+                )
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
 
-/// Casts suspension rays for all wheels and stores the hit information.
+/// Casts suspension rays for the wheels of each chassis and stores the hit
+/// information. Rays ignore the chassis' own colliders but can land on other
+/// vehicles.
 pub fn raycast_wheels(
     spatial: SpatialQuery,
-    tuning: Res<SuspensionTuning>,
-    chassis_q: Query<(&GlobalTransform, &Children), With<Chassis>>,
+    global: Res<SuspensionTuning>,
+    chassis_q: Query<
+        (Entity, &GlobalTransform, Option<&SuspensionTuning>, &Children),
+        With<Chassis>,
+    >,
+    colliders: Query<&ColliderOf>,
     mut wheels: Query<(&mut RaycastWheel, &mut Transform)>,
 ) {
-    let ray_len = tuning.rest_length + tuning.max_travel;
-    for (chassis_tf, children) in &chassis_q {
+    for (chassis, chassis_tf, tuning, children) in &chassis_q {
+        let not_own = |entity: Entity| colliders.get(entity).map_or(true, |c| c.body != chassis);
+        let tuning = tuning.unwrap_or(&global);
+        let ray_len = tuning.rest_length + tuning.max_travel;
         for child in children.iter() {
             if let Ok((mut wheel, mut tf)) = wheels.get_mut(child) {
                 let origin = chassis_tf.transform_point(wheel.mount);
                 let dir = chassis_tf.rotation() * Vec3::NEG_Y;
-                let result = spatial.cast_ray(
+                let result = spatial.cast_ray_predicate(
                     origin,
                     Dir3::new_unchecked(dir),
                     ray_len,
                     false,
                     &SpatialQueryFilter::default(),
+                    &not_own,
                 );
                 if let Some(hit) = result {
                     wheel.grounded = true;
@@ -126,9 +183,6 @@ pub fn raycast_wheels(
                     wheel.contact_normal = hit.normal;
                     wheel.prev_compression = wheel.compression;
                     wheel.compression = (tuning.rest_length - hit.distance).max(0.0);
-                    if wheel.compression < 0.1 {
-                        wheel.compression = 0.0;
-                    }
                     tf.translation = wheel.mount - Vec3::Y * wheel.compression;
                 } else {
                     wheel.grounded = false;
@@ -141,107 +195,188 @@ pub fn raycast_wheels(
     }
 }
 
-/// Applies spring and damping forces based on wheel compression.
+/// Chassis data needed to apply forces at its wheels.
+type ChassisForces = (
+    &'static GlobalTransform,
+    Option<&'static ComputedCenterOfMass>,
+    Option<&'static SuspensionTuning>,
+    &'static mut ExternalForce,
+    &'static Children,
+);
+
+/// Upper bound on a single suspension force (N), guarding against spikes
+/// when a wheel is suddenly fully compressed.
+const MAX_SUSPENSION_FORCE: f32 = 1.0e5;
+
+/// Applies spring and damping forces at each grounded wheel's contact point,
+/// so the chassis pitches and rolls as load shifts between wheels.
 pub fn apply_suspension(
     time: Res<Time>,
-    tuning: Res<SuspensionTuning>,
-    mut chassis_q: Query<(&mut LinearVelocity, &Chassis)>,
-    wheels: Query<&RaycastWheel>,
+    global: Res<SuspensionTuning>,
+    mut chassis_q: Query<ChassisForces, With<Chassis>>,
+    mut wheels: Query<&mut RaycastWheel>,
 ) {
-    const STEP: f32 = 1.0 / 60.0;
-    let dt      = time.delta_secs();
-    let steps   = (dt / STEP).ceil() as u32;
-    let sub_dt  = dt / steps as f32;
-
-    for _ in 0..steps {
-        for (mut lv, chassis) in &mut chassis_q {
-            for wheel in wheels.iter() {
-                if !wheel.grounded { continue; }
-
-                // plain spring-damper
-                let rel_vel   = (wheel.compression - wheel.prev_compression) / sub_dt;
-                let spring_f  =  tuning.k * wheel.compression;      // ↑ push up
-                let damper_f  =  tuning.c * rel_vel;                // ↑ resist motion
-                let mut force = spring_f + damper_f;
-
-                // never pull the wheel through the ground
-                if force < 0.0 { force = 0.0; }
-
-                let mut impulse = wheel.contact_normal * force * sub_dt;
-                let mag = impulse.length();
-                if !mag.is_finite() || mag > 1.0e5 {
-                    impulse *= 1.0e5 / mag.max(1.0e-6);
-                }
-
-                lv.0 += impulse / chassis.mass;
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    for (chassis_tf, com, tuning, mut force, children) in &mut chassis_q {
+        let tuning = tuning.unwrap_or(&global);
+        let com = chassis_tf.transform_point(com.map_or(Vec3::ZERO, |c| c.0));
+        for child in children.iter() {
+            let Ok(mut wheel) = wheels.get_mut(child) else { continue; };
+            if !wheel.grounded {
+                wheel.load = 0.0;
+                wheel.suspension_force = 0.0;
+                continue;
             }
+
+            // plain spring-damper
+            let rel_vel   = (wheel.compression - wheel.prev_compression) / dt;
+            let spring_f  =  tuning.k * wheel.compression;      // ↑ push up
+            let damper_f  =  tuning.c * rel_vel;                // ↑ resist motion
+
+            // never pull the wheel through the ground
+            wheel.suspension_force = (spring_f + damper_f).clamp(0.0, MAX_SUSPENSION_FORCE);
+            wheel.load = wheel.suspension_force;
+            force.apply_force_at_point(wheel.contact_normal * wheel.load, wheel.contact_point, com);
         }
     }
 }
 
-/// Computes tire friction forces and applies them as impulses.
+/// Directions of the longitudinal and lateral tire forces of `wheel` on a
+/// chassis with the given `rotation`: the steered wheel heading and its side
+/// axis, both in the ground plane at the contact.
+pub fn contact_axes(rotation: Quat, wheel: &RaycastWheel) -> (Vec3, Vec3) {
+    let normal = wheel.contact_normal;
+    let heading = rotation * Quat::from_rotation_y(wheel.steer_angle) * Vec3::Z;
+    let forward = (heading - normal * heading.dot(normal)).normalize_or_zero();
+    (forward, normal.cross(forward))
+}
+
+/// Spins each wheel from engine and brake torque and applies the resulting
+/// tire forces at its contact point. Longitudinal force comes from the slip
+/// ratio between wheel and ground, lateral force from the slip angle of the
+/// steered wheel (see [`crate::tire`]).
 pub fn compute_tire_forces(
     time: Res<Time>,
-    tuning: Res<SuspensionTuning>,
-    mut chassis_q: Query<(&mut LinearVelocity, &Chassis)>,
-    wheels: Query<&RaycastWheel>,
+    params: Res<GameParams>,
+    global: Res<SuspensionTuning>,
+    mut chassis_q: Query<
+        (
+            &Vehicle,
+            &LinearVelocity,
+            &AngularVelocity,
+            ChassisForces,
+        ),
+        With<Chassis>,
+    >,
+    mut wheels: Query<&mut RaycastWheel>,
 ) {
-    const STEP: f32 = 1.0 / 60.0;
     let dt = time.delta_secs();
-    let steps = (dt / STEP).ceil() as u32;
-    let sub_dt = dt / steps as f32;
-    for _ in 0..steps {
-        for (mut lv, chassis) in &mut chassis_q {
-            for wheel in wheels.iter() {
-                if !wheel.grounded { continue; }
-                let load = tuning.k * wheel.compression;
-                let long_force = -tuning.mu_long * load;
-                let lat_force = -tuning.mu_lat * load;
-                let mut impulse = (wheel.contact_normal.cross(Vec3::Y) * lat_force)
-                    + (wheel.contact_normal * long_force);
-                impulse *= sub_dt;
-                let mag = impulse.length();
-                if !mag.is_finite() || mag > 1.0e5 {
-                    let clamped = mag.max(1.0e-6).min(1.0e5);
-                    impulse *= clamped / mag;
-                }
-                lv.0 += impulse / chassis.mass;
+    if dt <= 0.0 {
+        return;
+    }
+    for (vehicle, lin_vel, ang_vel, (chassis_tf, com, tuning, mut force, children)) in &mut chassis_q {
+        let tuning = tuning.unwrap_or(&global);
+        let com = chassis_tf.transform_point(com.map_or(Vec3::ZERO, |c| c.0));
+        let rotation = chassis_tf.rotation();
+        for child in children.iter() {
+            let Ok(mut wheel) = wheels.get_mut(child) else { continue; };
+            wheel.steer_angle = if wheel.is_front {
+                -vehicle.steer * params.vehicle_max_steer
+            } else {
+                0.0
+            };
+            let inertia = wheel.inertia();
+
+            // Drive torque spins the wheel up; brake torque slows it down
+            // but never reverses it.
+            wheel.angular_velocity += wheel.drive_torque / inertia * dt;
+            let brake_dw = (wheel.brake_torque / inertia * dt).min(wheel.angular_velocity.abs());
+            wheel.angular_velocity -= brake_dw * wheel.angular_velocity.signum();
+
+            if !wheel.grounded {
+                wheel.slip_ratio = 0.0;
+                wheel.slip_angle = 0.0;
+                wheel.tire_force = TireForce::default();
+                continue;
             }
+
+            let (forward, side) = contact_axes(rotation, &wheel);
+
+            let point = wheel.contact_point;
+            let velocity = lin_vel.0 + ang_vel.0.cross(point - com);
+            let v_long = velocity.dot(forward);
+            let v_lat = velocity.dot(side);
+
+            let load = wheel.load;
+            let wheel_speed = wheel.angular_velocity * wheel.radius;
+            wheel.slip_ratio = slip_ratio(wheel_speed, v_long);
+            wheel.slip_angle = slip_angle(v_long, v_lat);
+            let tire = tire_force(
+                wheel.slip_ratio,
+                wheel.slip_angle,
+                load,
+                tuning.mu_long,
+                tuning.mu_lat,
+            );
+            wheel.tire_force = tire;
+
+            // The road pushes back on the wheel. The reaction can bring the
+            // wheel to rolling speed within a tick but not past it, which
+            // keeps the stiff slip curve stable at the fixed timestep.
+            let rolling = v_long / wheel.radius;
+            let reacted = wheel.angular_velocity - tire.longitudinal * wheel.radius / inertia * dt;
+            wheel.angular_velocity = if wheel.angular_velocity > rolling {
+                reacted.max(rolling)
+            } else {
+                reacted.min(rolling)
+            };
+
+            force.apply_force_at_point(
+                forward * tire.longitudinal + side * tire.lateral,
+                point,
+                com,
+            );
         }
     }
 }
 
-/// Applies an anti-roll torque across each axle.
+/// Couples the left and right wheels of each axle like an anti-roll bar:
+/// the more compressed side pushes the body up and the other side pulls it
+/// down by the same amount, resisting roll without lifting the car.
 pub fn apply_anti_roll(
-    tuning: Res<SuspensionTuning>,
-    mut chassis_q: Query<&mut AngularVelocity, With<Chassis>>,
-    wheels: Query<&RaycastWheel>,
+    global: Res<SuspensionTuning>,
+    mut chassis_q: Query<ChassisForces, With<Chassis>>,
+    mut wheels: Query<&mut RaycastWheel>,
 ) {
-    let mut front_l = None;
-    let mut front_r = None;
-    let mut rear_l = None;
-    let mut rear_r = None;
-    for wheel in &wheels {
-        match (wheel.is_front, wheel.is_left) {
-            (true, true) => front_l = Some(wheel.compression),
-            (true, false) => front_r = Some(wheel.compression),
-            (false, true) => rear_l = Some(wheel.compression),
-            (false, false) => rear_r = Some(wheel.compression),
-        }
-    }
-    for mut av in &mut chassis_q {
-        if let (Some(fl), Some(fr)) = (front_l, front_r) {
-            let delta = fl - fr;
-            av.0.z -= delta * tuning.k_anti_roll;
-        }
-        if let (Some(rl), Some(rr)) = (rear_l, rear_r) {
-            let delta = rl - rr;
-            av.0.z -= delta * tuning.k_anti_roll;
-        }
-        let mag = av.0.length();
-        if mag > 100.0 {
-            av.0 *= 100.0 / mag;
+    for (chassis_tf, com, tuning, mut force, children) in &mut chassis_q {
+        let tuning = tuning.unwrap_or(&global);
+        let com = chassis_tf.transform_point(com.map_or(Vec3::ZERO, |c| c.0));
+        let up = chassis_tf.up();
+        for front in [true, false] {
+            let mut left = None;
+            let mut right = None;
+            for child in children.iter() {
+                if let Ok(wheel) = wheels.get(child) {
+                    if wheel.is_front == front && wheel.grounded {
+                        if wheel.is_left {
+                            left = Some(child);
+                        } else {
+                            right = Some(child);
+                        }
+                    }
+                }
+            }
+            let (Some(left), Some(right)) = (left, right) else { continue; };
+            let Ok([mut left, mut right]) = wheels.get_many_mut([left, right]) else { continue; };
+
+            let bar = (left.compression - right.compression) * tuning.k_anti_roll;
+            force.apply_force_at_point(up * bar, left.contact_point, com);
+            force.apply_force_at_point(-up * bar, right.contact_point, com);
+            left.load = (left.load + bar).max(0.0);
+            right.load = (right.load - bar).max(0.0);
         }
     }
 }
-
@@ -0,0 +1,132 @@
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+/// How a weapon's shots travel.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FireMode {
+    /// Shots fly at `speed` and are moved every tick.
+    #[default]
+    Projectile,
+    /// Shots hit instantly along a ray up to `range`.
+    Hitscan,
+}
+
+/// Look of a weapon's shots.
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct WeaponVisuals {
+    /// Emissive colour; components above 1 glow.
+    pub color: [f32; 3],
+    /// Projectile size (m). Hitscan beams use its width and height.
+    pub size: [f32; 3],
+    /// Intensity of the light carried by projectiles, 0 for none.
+    pub light_intensity: f32,
+    pub light_range: f32,
+}
+
+impl Default for WeaponVisuals {
+    fn default() -> Self {
+        Self {
+            color: [5.0, 0.0, 0.0],
+            size: [0.05, 0.05, 0.3],
+            light_intensity: 1500.0,
+            light_range: 6.0,
+        }
+    }
+}
+
+/// Description of a weapon, stored as a `.weapon.ron` asset. Missing fields
+/// take the values of the default laser.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WeaponDef {
+    pub name: String,
+    pub mode: FireMode,
+    /// Projectile speed (m/s).
+    pub speed: f32,
+    /// Reach of hitscan shots (m).
+    pub range: f32,
+    /// Shots fired at once, e.g. shotgun pellets.
+    pub pellets: u32,
+    /// Half-angle of the cone shots are scattered in (rad).
+    pub spread: f32,
+    /// Damage per shot that hits.
+    pub damage: f32,
+    /// Weapon energy used per trigger pull, out of 1.
+    pub energy_cost: f32,
+    /// Trigger pulls per second while fire is held.
+    pub fire_rate: f32,
+    /// Times a shot ricochets before it is spent.
+    pub bounces: u32,
+    /// Share of speed kept on each bounce.
+    pub bounce_decay: f32,
+    /// Seconds a projectile flies, or a hitscan beam stays visible.
+    pub lifetime: f32,
+    pub visuals: WeaponVisuals,
+}
+
+impl Default for WeaponDef {
+    fn default() -> Self {
+        Self {
+            name: "Laser".to_string(),
+            mode: FireMode::Projectile,
+            speed: 100.0,
+            range: 100.0,
+            pellets: 1,
+            spread: 0.0,
+            damage: 5.0,
+            energy_cost: 0.02,
+            fire_rate: 50.0,
+            bounces: 8,
+            bounce_decay: 0.67,
+            lifetime: 0.5,
+            visuals: WeaponVisuals::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WeaponDefLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for WeaponDefLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeaponDefLoaderError::Io(e) => write!(f, "could not read weapon: {e}"),
+            WeaponDefLoaderError::Ron(e) => write!(f, "could not parse weapon: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for WeaponDefLoaderError {}
+
+#[derive(Default)]
+pub struct WeaponDefLoader;
+
+impl AssetLoader for WeaponDefLoader {
+    type Asset = WeaponDef;
+    type Settings = ();
+    type Error = WeaponDefLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(WeaponDefLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(WeaponDefLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapon.ron"]
+    }
+}
@@ -2,28 +2,36 @@ use bevy::prelude::*;
 use bevy::render::view::{Layer, RenderLayers};
 use bevy_svg::prelude::*;
 
+use crate::weapon_def::WeaponDef;
+use crate::weapons::Inventory;
 use crate::{hud::HUD_LAYER, input::Player};
 
-/// Plugin that displays the player's weapon charge.
+/// Plugin that displays the player's weapon charge and selected weapon.
 pub struct WeaponHudPlugin;
 
 impl Plugin for WeaponHudPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_weapon_hud)
-            .add_systems(Update, (update_weapon_charge, position_weapon_charge));
+            .add_systems(
+                Update,
+                (update_weapon_charge, update_weapon_name, position_weapon_charge),
+            );
     }
 }
 
 #[derive(Component)]
 struct WeaponChargeMeter;
 
+#[derive(Component)]
+struct WeaponName;
+
 fn setup_weapon_hud(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     windows: Query<&Window>,
 ) {
-    let window = windows.single();
-    let win_size = window.unwrap().resolution.physical_size();
+    let Ok(window) = windows.single() else { return; };
+    let win_size = window.resolution.physical_size();
 
     let bar = asset_server.load("gradient.svg");
 
@@ -38,6 +46,35 @@ fn setup_weapon_hud(
         RenderLayers::layer(HUD_LAYER as Layer),
         WeaponChargeMeter,
     ));
+
+    commands.spawn((
+        Text2d::default(),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor::WHITE,
+        Transform::default(),
+        RenderLayers::layer(HUD_LAYER as Layer),
+        WeaponName,
+    ));
+}
+
+fn update_weapon_name(
+    players: Query<&Inventory, With<Player>>,
+    weapons: Res<Assets<WeaponDef>>,
+    mut q: Query<&mut Text2d, With<WeaponName>>,
+) {
+    let Ok(inventory) = players.single() else { return; };
+    let name = inventory
+        .current()
+        .and_then(|handle| weapons.get(handle))
+        .map_or("", |def| def.name.as_str());
+    for mut text in &mut q {
+        if text.0 != name {
+            text.0 = name.to_string();
+        }
+    }
 }
 
 fn update_weapon_charge(
@@ -59,12 +96,20 @@ fn update_weapon_charge(
 
 fn position_weapon_charge(
     windows: Query<&Window>,
-    mut q: Query<&mut Transform, With<WeaponChargeMeter>>,
+    mut meters: Query<&mut Transform, (With<WeaponChargeMeter>, Without<WeaponName>)>,
+    mut names: Query<&mut Transform, With<WeaponName>>,
 ) {
-    let window = windows.single();
-    let size = window.unwrap().resolution.physical_size();
-    for mut tf in &mut q {
-        tf.translation.x = -(size.x as f32 / 4.0) + 20.0;
-        tf.translation.y = -(size.y as f32 / 4.0) + 150.0;
+    let Ok(window) = windows.single() else { return; };
+    let size = window.resolution.physical_size();
+    let x = -(size.x as f32 / 4.0) + 20.0;
+    let y = -(size.y as f32 / 4.0) + 150.0;
+    for mut tf in &mut meters {
+        tf.translation.x = x;
+        tf.translation.y = y;
+    }
+    // Text is centred on its position; keep it above the meter.
+    for mut tf in &mut names {
+        tf.translation.x = x + 50.0;
+        tf.translation.y = y + 30.0;
     }
 }
@@ -1,98 +1,343 @@
 use bevy::prelude::*;
+use rand::Rng;
+use std::collections::HashMap;
 
-use avian3d::prelude::{Collider, ShapeCastConfig, SpatialQuery, SpatialQueryFilter};
+use avian3d::prelude::{Collider, ColliderOf, ShapeCastConfig, SpatialQuery, SpatialQueryFilter};
 
-use crate::{globals::GameParams, input::Player};
+use crate::actions::{Action, ActionState};
+use crate::globals::{Controlled, InVehicle};
+use crate::health::{hit_multiplier, DamageEvent, Damageable, HitZone};
+use crate::weapon_def::{FireMode, WeaponDef, WeaponDefLoader};
+use crate::{fixed_step::InterpolatedTransform, game_state::GameState, input::Player};
+
+/// Weapons the player starts with, in inventory order.
+pub const PLAYER_WEAPONS: [&str; 4] = [
+    "weapons/laser.weapon.ron",
+    "weapons/shotgun.weapon.ron",
+    "weapons/rocket.weapon.ron",
+    "weapons/railgun.weapon.ron",
+];
+/// Hitscan rays restart this far (m) off a surface after bouncing.
+const BOUNCE_OFFSET: f32 = 0.01;
 
 pub struct WeaponPlugin;
 
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
-        app
-            .add_systems(Update, player_fire_system)
-            .add_systems(Update, laser_movement_system);
+        app.init_asset::<WeaponDef>()
+            .init_asset_loader::<WeaponDefLoader>()
+            .init_resource::<ShotAssets>()
+            .add_event::<FireEvent>()
+            // Switching reacts to a single press, so it stays in `Update`.
+            .add_systems(Update, switch_weapon.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                FixedUpdate,
+                (
+                    player_fire_system,
+                    fire_weapons,
+                    projectile_movement_system,
+                    tracer_fade_system,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-#[derive(Component)]
-pub struct Laser {
-    pub(crate) velocity: Vec3,
-    pub(crate) prev_position: Vec3,
-    pub(crate) projected_position: Vec3,
+/// Weapons carried by a player, one of which is selected.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Inventory {
+    pub weapons: Vec<Handle<WeaponDef>>,
+    pub selected: usize,
+}
+
+impl Inventory {
+    pub fn new(weapons: Vec<Handle<WeaponDef>>) -> Self {
+        Self { weapons, selected: 0 }
+    }
+
+    pub fn current(&self) -> Option<&Handle<WeaponDef>> {
+        self.weapons.get(self.selected)
+    }
+
+    /// Selects the next weapon, wrapping around after the last.
+    pub fn next(&mut self) {
+        if !self.weapons.is_empty() {
+            self.selected = (self.selected + 1) % self.weapons.len();
+        }
+    }
+
+    /// Selects the previous weapon, wrapping around before the first.
+    pub fn previous(&mut self) {
+        if !self.weapons.is_empty() {
+            self.selected = (self.selected + self.weapons.len() - 1) % self.weapons.len();
+        }
+    }
+}
+
+/// One trigger pull of `weapon`, fired from `origin` towards `direction`.
+/// Anything with a gun sends these; [`fire_weapons`] turns them into shots.
+#[derive(Event, Clone, Debug)]
+pub struct FireEvent {
+    pub shooter: Entity,
+    pub weapon: Handle<WeaponDef>,
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+/// Mesh and material of each weapon's shots, made on its first shot and
+/// shared by all later ones. The mesh is one metre long; shots stretch it
+/// with their scale.
+#[derive(Resource, Default)]
+pub struct ShotAssets(HashMap<AssetId<WeaponDef>, (Handle<Mesh>, Handle<StandardMaterial>)>);
+
+impl ShotAssets {
+    fn get(
+        &mut self,
+        id: AssetId<WeaponDef>,
+        def: &WeaponDef,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> (Handle<Mesh>, Handle<StandardMaterial>) {
+        self.0
+            .entry(id)
+            .or_insert_with(|| {
+                let [width, height, _] = def.visuals.size;
+                let [r, g, b] = def.visuals.color;
+                let material = materials.add(StandardMaterial {
+                    base_color: Color::srgb(r.min(1.0), g.min(1.0), b.min(1.0)),
+                    emissive: LinearRgba::from(Color::srgb(r, g, b)),
+                    ..default()
+                });
+                (meshes.add(Cuboid::new(width, height, 1.0)), material)
+            })
+            .clone()
+    }
+}
+
+/// Size and light of a shot, thinning out over its lifetime.
+struct Glow {
     life: f32,
-    material: Handle<StandardMaterial>,
+    lifetime: f32,
+    light_intensity: f32,
+    /// Scale of the shared shot mesh at full glow.
+    scale: Vec3,
+}
+
+impl Glow {
+    /// Ages the glow by `dt` and thins the shot, returning false once it is
+    /// spent.
+    fn fade(&mut self, dt: f32, tf: &mut Transform, light: Option<Mut<PointLight>>) -> bool {
+        self.life -= dt;
+        let ratio = (self.life / self.lifetime).clamp(0.0, 1.0);
+        if let Some(mut light) = light {
+            light.intensity = self.light_intensity * ratio;
+        }
+        tf.scale = self.scale * Vec3::new(ratio, ratio, 1.0);
+        self.life > 0.0
+    }
+}
+
+/// A shot in flight.
+#[derive(Component)]
+pub struct Projectile {
+    pub shooter: Entity,
+    pub damage: f32,
+    velocity: Vec3,
+    bounces_left: u32,
+    bounce_decay: f32,
+    half_size: Vec3,
+    glow: Glow,
+}
+
+/// Beam left behind by a hitscan shot.
+#[derive(Component)]
+pub struct Tracer {
+    glow: Glow,
 }
 
-pub const LASER_SPEED: f32 = 100.0;
-pub const LASER_LIFETIME: f32 = 0.5; // seconds
-pub const LASER_LIGHT_INTENSITY: f32 = 1500.0;
-pub const LASER_BOUNCE_DECAY: f32 = 0.67;
+/// Whether `entity` is `body` or one of its colliders, so shots pass
+/// through whoever fired them.
+fn is_part_of(colliders: &Query<&ColliderOf>, entity: Entity, body: Entity) -> bool {
+    entity == body || colliders.get(entity).is_ok_and(|c| c.body == body)
+}
+
+/// Turns `direction` by a random angle of up to `spread` (rad).
+pub fn scatter(direction: Vec3, spread: f32, rng: &mut impl Rng) -> Vec3 {
+    if spread <= 0.0 {
+        return direction;
+    }
+    let tilt = Quat::from_axis_angle(direction.any_orthonormal_vector(), rng.gen_range(0.0..=spread));
+    let roll = Quat::from_axis_angle(direction, rng.gen_range(0.0..std::f32::consts::TAU));
+    roll * tilt * direction
+}
+
+fn switch_weapon(actions: Res<ActionState>, mut inventories: Query<&mut Inventory, With<Controlled>>) {
+    for mut inventory in &mut inventories {
+        if actions.just_pressed(Action::NextWeapon) {
+            inventory.next();
+        }
+        if actions.just_pressed(Action::PreviousWeapon) {
+            inventory.previous();
+        }
+    }
+}
 
 fn player_fire_system(
     time: Res<Time>,
-    keys: Res<ButtonInput<KeyCode>>,
-    params: Res<GameParams>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut players: Query<(&Transform, &mut Player)>,
+    actions: Res<ActionState>,
+    weapons: Res<Assets<WeaponDef>>,
+    mut players: Query<(Entity, &Transform, &mut Player, &Inventory, Has<InVehicle>)>,
+    mut shots: EventWriter<FireEvent>,
 ) {
     let dt = time.delta_secs();
     let recharge_rate = dt / 3.0;
-    let fire_cost = 1.0 / (params.fire_rate * 1.0);
-    for (tf, mut plyr) in &mut players {
+    for (entity, tf, mut plyr, inventory, in_vehicle) in &mut players {
         if plyr.fire_timer > 0.0 {
             plyr.fire_timer -= dt;
         }
         plyr.weapon_energy = (plyr.weapon_energy + recharge_rate).min(1.0);
-        if keys.pressed(KeyCode::Space) && plyr.fire_timer <= 0.0 {
-            if plyr.weapon_energy < fire_cost {
-                continue;
+        let Some(handle) = inventory.current() else { continue; };
+        let Some(def) = weapons.get(handle) else { continue; };
+        // Seated players' Fire goes to the vehicle's turrets instead.
+        if in_vehicle
+            || !actions.pressed(Action::Fire)
+            || plyr.fire_timer > 0.0
+            || plyr.weapon_energy < def.energy_cost
+        {
+            continue;
+        }
+        let forward = tf.rotation * Vec3::Z;
+        shots.write(FireEvent {
+            shooter: entity,
+            weapon: handle.clone(),
+            origin: tf.translation + forward * (plyr.half_extents.z + 1.0),
+            direction: forward,
+        });
+        plyr.fire_timer = 1.0 / def.fire_rate.max(f32::EPSILON);
+        plyr.weapon_energy -= def.energy_cost;
+    }
+}
+
+/// Spawns the projectiles of each [`FireEvent`], or traces its hitscan rays
+/// and damages what they hit.
+#[allow(clippy::too_many_arguments)]
+pub fn fire_weapons(
+    mut events: EventReader<FireEvent>,
+    weapons: Res<Assets<WeaponDef>>,
+    spatial: SpatialQuery,
+    mut commands: Commands,
+    mut shot_assets: ResMut<ShotAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    colliders: Query<&ColliderOf>,
+    damageable: Query<(), With<Damageable>>,
+    zones: Query<&HitZone>,
+    mut damage: EventWriter<DamageEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    for shot in events.read() {
+        let Some(def) = weapons.get(&shot.weapon) else { continue; };
+        let Ok(aim) = Dir3::new(shot.direction) else { continue; };
+        let [r, g, b] = def.visuals.color;
+        let (mesh, material) = shot_assets.get(shot.weapon.id(), def, &mut meshes, &mut materials);
+        let glow = |length| Glow {
+            life: def.lifetime,
+            lifetime: def.lifetime.max(f32::EPSILON),
+            light_intensity: def.visuals.light_intensity,
+            scale: Vec3::new(1.0, 1.0, length),
+        };
+        for _ in 0..def.pellets.max(1) {
+            let direction = scatter(aim.as_vec3(), def.spread, &mut rng);
+            match def.mode {
+                FireMode::Projectile => {
+                    let pos = shot.origin;
+                    let length = def.visuals.size[2];
+                    let mut projectile = commands.spawn(Mesh3d(mesh.clone()));
+                    projectile
+                        .insert(MeshMaterial3d(material.clone()))
+                        .insert(
+                            Transform::from_translation(pos)
+                                .looking_at(pos + direction, Vec3::Y)
+                                .with_scale(Vec3::new(1.0, 1.0, length)),
+                        )
+                        .insert(InterpolatedTransform::default())
+                        .insert(Projectile {
+                            shooter: shot.shooter,
+                            damage: def.damage,
+                            velocity: direction * def.speed,
+                            bounces_left: def.bounces,
+                            bounce_decay: def.bounce_decay,
+                            half_size: Vec3::from_array(def.visuals.size) * 0.5,
+                            glow: glow(length),
+                        });
+                    if def.visuals.light_intensity > 0.0 {
+                        projectile.insert(PointLight {
+                            intensity: def.visuals.light_intensity,
+                            range: def.visuals.light_range,
+                            color: Color::srgb(r, g, b),
+                            ..default()
+                        });
+                    }
+                }
+                FireMode::Hitscan => {
+                    let filter = SpatialQueryFilter::from_excluded_entities([shot.shooter]);
+                    let mut start = shot.origin;
+                    let mut direction = direction;
+                    let mut range = def.range;
+                    for _ in 0..=def.bounces {
+                        let Ok(dir) = Dir3::new(direction) else { break; };
+                        let hit = spatial.cast_ray_predicate(start, dir, range, true, &filter, &|entity| {
+                            !is_part_of(&colliders, entity, shot.shooter)
+                        });
+                        let end = start + direction * hit.map_or(range, |h| h.distance);
+                        let length = start.distance(end);
+                        commands
+                            .spawn(Mesh3d(mesh.clone()))
+                            .insert(MeshMaterial3d(material.clone()))
+                            .insert(
+                                Transform::from_translation(start.midpoint(end))
+                                    .looking_at(end, Vec3::Y)
+                                    .with_scale(Vec3::new(1.0, 1.0, length)),
+                            )
+                            .insert(Tracer { glow: glow(length) });
+                        let Some(hit) = hit else { break; };
+                        let body = colliders.get(hit.entity).map_or(hit.entity, |c| c.body);
+                        if damageable.contains(body) {
+                            damage.write(DamageEvent {
+                                source: Some(shot.shooter),
+                                target: body,
+                                amount: def.damage * hit_multiplier(&zones, hit.entity, body),
+                                point: Some(end),
+                                normal: Some(hit.normal),
+                            });
+                        }
+                        range -= hit.distance;
+                        direction = direction.reflect(hit.normal);
+                        start = end + hit.normal * BOUNCE_OFFSET;
+                    }
+                }
             }
-            let forward = tf.rotation * Vec3::Z;
-            let pos = tf.translation + forward * (plyr.half_extents.z + 1.0);
-            let mesh = meshes.add(Cuboid::new(0.05, 0.05, 0.3));
-            let material = materials.add(StandardMaterial {
-                base_color: Color::srgb(1.0, 0.0, 0.0),
-                emissive: LinearRgba::from(Color::srgb(5.0, 0.0, 0.0)),
-                ..default()
-            });
-            commands
-                .spawn(Mesh3d(mesh))
-                .insert(MeshMaterial3d(material.clone()))
-                .insert(PointLight {
-                    intensity: LASER_LIGHT_INTENSITY,
-                    range: 6.0,
-                    color: Color::srgb(5.0, 0.0, 0.0),
-                    ..default()
-                })
-                .insert(Transform::from_translation(pos).looking_at(pos + forward, Vec3::Y))
-                .insert(Laser {
-                    velocity: forward * LASER_SPEED,
-                    prev_position: pos,
-                    projected_position: pos,
-                    life: LASER_LIFETIME,
-                    material,
-                });
-            plyr.fire_timer = 1.0 / params.fire_rate.max(f32::EPSILON);
-            plyr.weapon_energy -= fire_cost;
         }
     }
 }
 
-pub fn laser_movement_system(
+#[allow(clippy::too_many_arguments)]
+pub fn projectile_movement_system(
     time: Res<Time>,
     spatial: SpatialQuery,
     mut commands: Commands,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut q: Query<(Entity, &mut Transform, &mut Laser, &mut PointLight)>,
+    mut q: Query<(Entity, &mut Transform, &mut Projectile, Option<&mut PointLight>)>,
+    colliders: Query<&ColliderOf>,
+    damageable: Query<(), With<Damageable>>,
+    zones: Query<&HitZone>,
+    mut damage: EventWriter<DamageEvent>,
 ) {
     let dt = time.delta_secs();
-    let col = Collider::cuboid(0.025, 0.025, 0.15);
-    for (e, mut tf, mut laser, mut light) in &mut q {
-        let start_pos = tf.translation;
-        laser.projected_position = tf.translation + laser.velocity * dt;
-        let mut remaining = laser.velocity * dt;
+    for (e, mut tf, mut projectile, light) in &mut q {
+        let col = Collider::cuboid(projectile.half_size.x, projectile.half_size.y, projectile.half_size.z);
+        let mut remaining = projectile.velocity * dt;
+        let mut spent = false;
         let filter = SpatialQueryFilter::default().with_excluded_entities([e]);
         for _ in 0..2 {
             let dist = remaining.length();
@@ -100,7 +345,7 @@ pub fn laser_movement_system(
                 break;
             }
             let dir = Dir3::new_unchecked(remaining / dist);
-            match spatial.cast_shape(
+            match spatial.cast_shape_predicate(
                 &col,
                 tf.translation,
                 tf.rotation,
@@ -110,14 +355,28 @@ pub fn laser_movement_system(
                     ..Default::default()
                 },
                 &filter,
+                &|entity| !is_part_of(&colliders, entity, projectile.shooter),
             ) {
                 Some(hit) => {
                     tf.translation += dir.as_vec3() * hit.distance.max(0.0);
+                    let body = colliders.get(hit.entity).map_or(hit.entity, |c| c.body);
+                    if damageable.contains(body) {
+                        damage.write(DamageEvent {
+                            source: Some(projectile.shooter),
+                            target: body,
+                            amount: projectile.damage * hit_multiplier(&zones, hit.entity, body),
+                            point: Some(hit.point1),
+                            normal: Some(hit.normal1),
+                        });
+                    }
+                    if projectile.bounces_left == 0 {
+                        spent = true;
+                        break;
+                    }
+                    projectile.bounces_left -= 1;
                     let normal = hit.normal1;
-                    laser.velocity =
-                        (laser.velocity - 2.0 * laser.velocity.dot(normal) * normal)
-                            * LASER_BOUNCE_DECAY;
-                    remaining = laser.velocity * ((dist - hit.distance) / dist);
+                    projectile.velocity = projectile.velocity.reflect(normal) * projectile.bounce_decay;
+                    remaining = projectile.velocity * ((dist - hit.distance) / dist);
                 }
                 None => {
                     tf.translation += remaining;
@@ -126,21 +385,25 @@ pub fn laser_movement_system(
             }
         }
 
-        if laser.velocity.length_squared() > 0.0 {
-            let look_at_pos = tf.translation + laser.velocity;
+        if projectile.velocity.length_squared() > 0.0 {
+            let look_at_pos = tf.translation + projectile.velocity;
             tf.look_at(look_at_pos, Vec3::Y);
         }
 
-        laser.life -= dt;
-        let ratio = (laser.life / LASER_LIFETIME).clamp(0.0, 1.0);
-        light.intensity = LASER_LIGHT_INTENSITY * ratio;
-        if let Some(mat) = materials.get_mut(&laser.material) {
-            mat.emissive = LinearRgba::from(Color::srgb(5.0 * ratio, 0.0, 0.0));
-        }
-        if laser.life <= 0.0 {
+        if !projectile.glow.fade(dt, &mut tf, light) || spent {
             commands.entity(e).despawn();
         }
+    }
+}
 
-        laser.prev_position = start_pos;
+fn tracer_fade_system(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut tracers: Query<(Entity, &mut Transform, &mut Tracer)>,
+) {
+    for (entity, mut tf, mut tracer) in &mut tracers {
+        if !tracer.glow.fade(time.delta_secs(), &mut tf, None) {
+            commands.entity(entity).despawn();
+        }
     }
 }
@@ -1,5 +1,9 @@
 use crate::input::Player;
+use crate::fixed_step::InterpolatedTransform;
 use crate::globals::Controlled;
+use crate::health::{Damageable, Health, Impact, PLAYER_HEALTH};
+use crate::weapons::{Inventory, PLAYER_WEAPONS};
+use crate::level::{process_level_requests, LevelDef, LevelEntity, LevelLoaded};
 use avian3d::prelude::{Collider, ColliderConstructor, ColliderConstructorHierarchy};
 use avian3d::prelude::{LinearVelocity, RigidBody};
 use bevy::prelude::*;
@@ -8,7 +12,29 @@ pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_world);
+        app.add_systems(Startup, setup_world)
+            .add_systems(Update, spawn_level_terrain.after(process_level_requests));
+    }
+}
+
+fn spawn_level_terrain(
+    mut commands: Commands,
+    mut events: EventReader<LevelLoaded>,
+    levels: Res<Assets<LevelDef>>,
+    asset_server: Res<AssetServer>,
+) {
+    for ev in events.read() {
+        let Some(level) = levels.get(&ev.handle) else { continue; };
+        let terrain: Handle<Scene> = asset_server.load(level.terrain.clone());
+        commands
+            .spawn(SceneRoot(terrain))
+            .insert(Transform::from_xyz(0.0, 0.0, 0.0))
+            .insert(GlobalTransform::default())
+            .insert(ColliderConstructorHierarchy::new(
+                ColliderConstructor::TrimeshFromMesh,
+            ))
+            .insert(RigidBody::Static)
+            .insert(LevelEntity);
     }
 }
 
@@ -18,16 +44,6 @@ fn setup_world(
     mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>,
 ) {
-    let terrain: Handle<Scene> = asset_server.load("models/terrain.glb#Scene0");
-    commands
-        .spawn(SceneRoot(terrain))
-        .insert(Transform::from_xyz(0.0, 0.0, 0.0))
-        .insert(GlobalTransform::default())
-        .insert(ColliderConstructorHierarchy::new(
-            ColliderConstructor::TrimeshFromMesh,
-        ))
-        .insert(RigidBody::Static);
-
     commands.insert_resource(AmbientLight {
         brightness: 1000.0,
         ..default()
@@ -57,5 +73,12 @@ fn setup_world(
             fire_timer: 0.0,
             weapon_energy: 1.0,
         })
+        .insert(Health::new(PLAYER_HEALTH))
+        .insert(Damageable)
+        .insert(Impact::default())
+        .insert(Inventory::new(
+            PLAYER_WEAPONS.iter().map(|path| asset_server.load(*path)).collect(),
+        ))
+        .insert(InterpolatedTransform::default())
         .insert(Controlled);
 }
@@ -0,0 +1,118 @@
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+
+use game_demo::actions::{
+    Action, ActionBindings, ActionState, ActionsPlugin, BindingsFile, InputBinding, PointerOverUi,
+};
+
+fn app() -> App {
+    let mut app = App::new();
+    app.insert_resource(BindingsFile(None))
+        .add_plugins((MinimalPlugins, InputPlugin, ActionsPlugin));
+    app
+}
+
+fn press(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+}
+
+#[test]
+fn keys_drive_actions() {
+    let mut app = app();
+    press(&mut app, KeyCode::KeyW);
+    press(&mut app, KeyCode::KeyA);
+    app.update();
+
+    let state = app.world().resource::<ActionState>();
+    assert_eq!(state.value(Action::Accelerate), 1.0);
+    assert_eq!(state.value(Action::Steer), -1.0);
+    assert_eq!(state.value(Action::Brake), 0.0);
+    assert!(state.pressed(Action::Accelerate));
+}
+
+#[test]
+fn just_pressed_lasts_one_frame() {
+    let mut app = app();
+    press(&mut app, KeyCode::KeyE);
+    app.update();
+    assert!(app.world().resource::<ActionState>().just_pressed(Action::EnterExit));
+
+    app.update();
+    let state = app.world().resource::<ActionState>();
+    assert!(state.pressed(Action::EnterExit));
+    assert!(!state.just_pressed(Action::EnterExit));
+}
+
+#[test]
+fn opposite_keys_cancel_out() {
+    let mut app = app();
+    press(&mut app, KeyCode::KeyA);
+    press(&mut app, KeyCode::KeyD);
+    app.update();
+    assert_eq!(app.world().resource::<ActionState>().value(Action::Steer), 0.0);
+}
+
+#[test]
+fn custom_binding_replaces_default() {
+    let mut app = app();
+    {
+        let mut bindings = app.world_mut().resource_mut::<ActionBindings>();
+        bindings.clear(Action::Fire);
+        bindings.add(Action::Fire, InputBinding::Key(KeyCode::KeyF));
+    }
+    press(&mut app, KeyCode::Space);
+    app.update();
+    assert!(!app.world().resource::<ActionState>().pressed(Action::Fire));
+
+    press(&mut app, KeyCode::KeyF);
+    app.update();
+    assert!(app.world().resource::<ActionState>().pressed(Action::Fire));
+}
+
+#[test]
+fn mouse_is_ignored_over_the_ui() {
+    let mut app = app();
+    app.world_mut().resource_mut::<PointerOverUi>().0 = true;
+    app.world_mut()
+        .resource_mut::<ButtonInput<MouseButton>>()
+        .press(MouseButton::Left);
+    app.update();
+    assert!(!app.world().resource::<ActionState>().pressed(Action::Fire));
+
+    app.world_mut().resource_mut::<PointerOverUi>().0 = false;
+    app.update();
+    assert!(app.world().resource::<ActionState>().pressed(Action::Fire));
+}
+
+#[test]
+fn bindings_round_trip_through_ron() {
+    let mut bindings = ActionBindings::default();
+    bindings.add(
+        Action::Steer,
+        InputBinding::KeyAxis {
+            negative: KeyCode::KeyJ,
+            positive: KeyCode::KeyL,
+        },
+    );
+    let text = ron::to_string(&bindings).unwrap();
+    let parsed: ActionBindings = ron::from_str(&text).unwrap();
+    assert_eq!(parsed, bindings);
+}
+
+#[test]
+fn bindings_file_saves_and_loads() {
+    let path = std::env::temp_dir().join(format!("bindings-{}.ron", std::process::id()));
+    let file = BindingsFile(Some(path.clone()));
+    let mut bindings = ActionBindings::default();
+    bindings.clear(Action::Fire);
+    file.save(&bindings).unwrap();
+    assert_eq!(file.load(), bindings);
+    std::fs::remove_file(path).unwrap();
+
+    // Without a file nothing is written and the defaults apply.
+    let memory = BindingsFile(None);
+    memory.save(&bindings).unwrap();
+    assert_eq!(memory.load(), ActionBindings::default());
+}
@@ -0,0 +1,86 @@
+use game_demo::brakes::Brakes;
+use game_demo::drift::DriftState;
+
+const DT: f32 = 1.0 / 60.0;
+
+#[test]
+fn brake_bias_splits_torque_between_axles() {
+    let brakes = Brakes {
+        abs: false,
+        ..Default::default()
+    };
+    let front = brakes.wheel_torque(1.0, 0.0, true, 0.0);
+    let rear = brakes.wheel_torque(1.0, 0.0, false, 0.0);
+    assert!((2.0 * (front + rear) - brakes.max_torque).abs() < 1e-3);
+    assert!((front / (front + rear) - brakes.front_bias).abs() < 1e-6);
+}
+
+#[test]
+fn abs_releases_locking_wheels() {
+    let brakes = Brakes::default();
+    assert!(brakes.wheel_torque(1.0, 0.0, true, -0.05) > 0.0);
+    assert_eq!(brakes.wheel_torque(1.0, 0.0, true, -0.5), 0.0);
+    // Reversing, a locking wheel's slip ratio is positive.
+    assert!(brakes.wheel_torque(1.0, 0.0, true, 0.05) > 0.0);
+    assert_eq!(brakes.wheel_torque(1.0, 0.0, true, 0.5), 0.0);
+
+    let no_abs = Brakes {
+        abs: false,
+        ..brakes
+    };
+    assert!(no_abs.wheel_torque(1.0, 0.0, true, -0.5) > 0.0);
+}
+
+#[test]
+fn handbrake_acts_on_rear_wheels_only() {
+    let brakes = Brakes::default();
+    assert_eq!(brakes.wheel_torque(0.0, 1.0, true, 0.0), 0.0);
+    // Still applied on a locked wheel, ABS or not.
+    assert_eq!(brakes.wheel_torque(0.0, 1.0, false, -1.0), brakes.handbrake_torque);
+}
+
+/// Feeds `seconds` of constant travel into `state`, returning the last result.
+fn drive(state: &mut DriftState, angle: f32, speed: f32, seconds: f32) -> Option<(f32, f32, f32)> {
+    let mut result = None;
+    for _ in 0..(seconds / DT).round() as usize {
+        result = result.or(state.update(angle, speed, DT));
+    }
+    result
+}
+
+#[test]
+fn drift_is_reported_once_it_ends() {
+    let mut state = DriftState::default();
+    assert!(drive(&mut state, 0.5, 15.0, 2.0).is_none());
+    assert!(state.active);
+    let (angle, duration, speed) = drive(&mut state, 0.0, 15.0, 1.0).unwrap();
+    assert_eq!(angle, 0.5);
+    assert!((duration - 2.0).abs() < 0.05);
+    assert!((speed - 15.0).abs() < 1e-3);
+    assert!(!state.active);
+}
+
+#[test]
+fn short_slow_or_spun_drifts_do_not_count() {
+    let mut state = DriftState::default();
+    assert!(drive(&mut state, 0.5, 15.0, 0.3).is_none());
+    assert!(drive(&mut state, 0.0, 15.0, 1.0).is_none(), "too short");
+
+    assert!(drive(&mut state, 0.5, 3.0, 2.0).is_none());
+    assert!(!state.active, "too slow");
+
+    drive(&mut state, 0.5, 15.0, 2.0);
+    assert!(drive(&mut state, 2.5, 15.0, 0.5).is_none());
+    assert!(!state.active, "spun out");
+}
+
+#[test]
+fn brief_straightening_keeps_the_drift_alive() {
+    let mut state = DriftState::default();
+    drive(&mut state, 0.5, 15.0, 1.0);
+    drive(&mut state, 0.0, 15.0, 0.2);
+    drive(&mut state, -0.6, 15.0, 1.0);
+    let (angle, duration, _) = drive(&mut state, 0.0, 15.0, 1.0).unwrap();
+    assert_eq!(angle, 0.6);
+    assert!(duration > 1.9);
+}
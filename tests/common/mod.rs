@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+use game_demo::game_state::GameState;
+use game_demo::globals::GameParams;
+use game_demo::headless::HeadlessPlugin;
+use game_demo::plugins::SimulationPlugins;
+use game_demo::vehicle::Vehicle;
+use game_demo::vehicle_systems::SuspensionTuning;
+
+/// The game without a window, as a dedicated server runs it.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin::default())
+        .insert_resource(GameParams::default())
+        .insert_resource(SuspensionTuning::default())
+        .add_plugins(SimulationPlugins);
+    app
+}
+
+/// Updates until the level has loaded and gameplay started.
+pub fn run_until_playing(app: &mut App) {
+    for _ in 0..2000 {
+        app.update();
+        if *app.world().resource::<State<GameState>>().get() == GameState::Playing {
+            return;
+        }
+        // Assets load on background threads.
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("level never finished loading");
+}
+
+/// Updates until the level's vehicles have been built.
+#[allow(dead_code)]
+pub fn run_until_vehicles(app: &mut App) {
+    for _ in 0..2000 {
+        app.update();
+        let world = app.world_mut();
+        if world.query::<&Vehicle>().iter(world).count() == 2 {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("level vehicles were never built");
+}
@@ -0,0 +1,103 @@
+use game_demo::config::{CliArgs, ConfigError, GameConfig};
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn cli_overrides_game_params() {
+    let (cli, errors) = CliArgs::parse(args(&[
+        "--socket-url",
+        "ws://localhost:8080",
+        "--max-speed=60",
+        "--k",
+        "50000",
+    ]));
+    assert!(errors.is_empty());
+    let mut config = GameConfig::default();
+    cli.apply(&mut config).unwrap();
+    assert_eq!(config.params.socket_url, "ws://localhost:8080");
+    assert_eq!(config.params.max_speed, 60.0);
+    assert_eq!(config.suspension.k, 50000.0);
+}
+
+#[test]
+fn config_path_flag() {
+    let (cli, _) = CliArgs::parse(args(&["--config", "custom.ron"]));
+    assert_eq!(cli.config_path().to_str(), Some("custom.ron"));
+    assert!(cli.overrides.is_empty());
+}
+
+#[test]
+fn headless_flag_takes_no_value() {
+    let (cli, _) = CliArgs::parse(args(&["--headless", "--max-speed", "60"]));
+    assert!(cli.headless);
+    assert_eq!(cli.overrides, vec![("max_speed".to_string(), "60".to_string())]);
+}
+
+#[test]
+fn unknown_and_invalid_overrides_fail() {
+    let mut config = GameConfig::default();
+    assert!(matches!(
+        config.set("warp_factor", "9"),
+        Err(ConfigError::UnknownArgument(_))
+    ));
+    assert!(matches!(
+        config.set("max_speed", "fast"),
+        Err(ConfigError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        CliArgs::parse(args(&["--max-speed"])).1[..],
+        [ConfigError::MissingValue(_)]
+    ));
+}
+
+#[test]
+fn bad_arguments_are_skipped_alone() {
+    let (cli, errors) = CliArgs::parse(args(&[
+        "stray",
+        "--config",
+        "custom.ron",
+        "--warp-factor",
+        "9",
+        "--max-speed",
+        "fast",
+        "--k",
+        "50000",
+        "--socket-url",
+    ]));
+    assert!(matches!(
+        errors[..],
+        [ConfigError::UnknownArgument(_), ConfigError::MissingValue(_)]
+    ));
+    assert_eq!(cli.config_path().to_str(), Some("custom.ron"));
+
+    let mut config = GameConfig::default();
+    let errors = cli.apply(&mut config).unwrap_err();
+    assert!(matches!(
+        errors[..],
+        [ConfigError::UnknownArgument(_), ConfigError::InvalidValue { .. }]
+    ));
+    assert_eq!(config.suspension.k, 50000.0);
+}
+
+#[test]
+fn partial_ron_uses_defaults() {
+    let config: GameConfig = ron::from_str("(params: (max_speed: 12.0))").unwrap();
+    assert_eq!(config.params.max_speed, 12.0);
+    assert_eq!(config.params.acceleration, GameConfig::default().params.acceleration);
+}
+
+#[test]
+fn config_plugin_uses_its_own_args() {
+    use bevy::prelude::*;
+    use game_demo::config::ConfigPlugin;
+    use game_demo::globals::GameParams;
+
+    let missing = std::env::temp_dir().join("game_demo_missing_config.ron");
+    let mut app = App::new();
+    app.add_plugins(ConfigPlugin {
+        args: args(&["--config", missing.to_str().unwrap(), "--max-speed", "60"]),
+    });
+    assert_eq!(app.world().resource::<GameParams>().max_speed, 60.0);
+}
@@ -0,0 +1,93 @@
+use game_demo::drivetrain::{Differential, DriveLayout, Drivetrain, Gearbox, TorqueCurve};
+
+const DT: f32 = 1.0 / 60.0;
+
+/// Steps `drivetrain` for `seconds` with the wheels held at `wheel_speed`.
+fn run(drivetrain: &mut Drivetrain, throttle: f32, wheel_speed: f32, seconds: f32) -> f32 {
+    let mut torque = 0.0;
+    for _ in 0..(seconds / DT).round() as usize {
+        torque = drivetrain.step(throttle, wheel_speed, DT);
+    }
+    torque
+}
+
+#[test]
+fn torque_curve_interpolates_and_holds_ends() {
+    let curve = TorqueCurve(vec![(1000.0, 100.0), (3000.0, 300.0)]);
+    assert_eq!(curve.torque_at(500.0), 100.0);
+    assert_eq!(curve.torque_at(2000.0), 200.0);
+    assert_eq!(curve.torque_at(9000.0), 300.0);
+    assert_eq!(TorqueCurve(Vec::new()).torque_at(2000.0), 0.0);
+}
+
+#[test]
+fn automatic_pulls_away_in_first_gear() {
+    let mut drivetrain = Drivetrain::default();
+    let torque = run(&mut drivetrain, 1.0, 0.0, 1.0);
+    assert_eq!(drivetrain.gear, 1);
+    assert_eq!(drivetrain.clutch, 1.0);
+    assert!(torque > 0.0);
+}
+
+#[test]
+fn automatic_shifts_on_rpm() {
+    let mut drivetrain = Drivetrain::default();
+    run(&mut drivetrain, 1.0, 0.0, 1.0);
+    // Wheels fast enough to pass the upshift point in first gear.
+    let fast = drivetrain.upshift_rpm / drivetrain.ratio(1) / 60.0 * std::f32::consts::TAU * 1.1;
+    run(&mut drivetrain, 1.0, fast, 0.1);
+    assert_eq!(drivetrain.gear, 2);
+    run(&mut drivetrain, 1.0, 1.0, 2.0);
+    assert_eq!(drivetrain.gear, 1);
+}
+
+#[test]
+fn automatic_reverses_on_negative_throttle() {
+    let mut drivetrain = Drivetrain::default();
+    let torque = run(&mut drivetrain, -1.0, 0.0, 1.0);
+    assert_eq!(drivetrain.gear, -1);
+    assert!(torque < 0.0);
+}
+
+#[test]
+fn manual_holds_gear_and_limits_revs() {
+    let mut drivetrain = Drivetrain {
+        gearbox: Gearbox::Manual,
+        ..Default::default()
+    };
+    assert_eq!(run(&mut drivetrain, 1.0, 0.0, 1.0), 0.0, "neutral");
+    drivetrain.shift_to(1);
+    let redline = drivetrain.redline_rpm / drivetrain.ratio(1) / 60.0 * std::f32::consts::TAU;
+    let torque = run(&mut drivetrain, 1.0, redline * 1.2, 1.0);
+    assert_eq!(drivetrain.gear, 1);
+    assert_eq!(drivetrain.rpm, drivetrain.redline_rpm);
+    assert!(torque <= 0.0, "rev limiter cuts drive");
+}
+
+#[test]
+fn shifting_opens_the_clutch() {
+    let mut drivetrain = Drivetrain::default();
+    run(&mut drivetrain, 1.0, 0.0, 1.0);
+    drivetrain.shift_to(2);
+    assert!(drivetrain.is_shifting());
+    assert_eq!(drivetrain.step(1.0, 10.0, DT), 0.0);
+}
+
+#[test]
+fn layouts_share_torque_between_axles() {
+    assert_eq!(DriveLayout::Fwd.axle_share(true, 0.4), 1.0);
+    assert_eq!(DriveLayout::Fwd.axle_share(false, 0.4), 0.0);
+    assert_eq!(DriveLayout::Rwd.axle_share(true, 0.4), 0.0);
+    assert_eq!(DriveLayout::Awd.axle_share(true, 0.4), 0.4);
+    assert_eq!(DriveLayout::Awd.axle_share(false, 0.4), 0.6);
+}
+
+#[test]
+fn limited_slip_favours_the_slower_wheel() {
+    assert_eq!(Differential::Open.split(100.0, 0.0, 20.0), (50.0, 50.0));
+    let lsd = Differential::LimitedSlip { locking: 0.5 };
+    let (left, right) = lsd.split(100.0, 0.0, 20.0);
+    assert_eq!(left + right, 100.0);
+    assert_eq!((left, right), (75.0, 25.0));
+    assert_eq!(lsd.split(100.0, 5.0, 5.0), (50.0, 50.0));
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use game_demo::config::ConfigPlugin;
+use game_demo::fixed_step::{FixedStepPlugin, InterpolatedTransform, InterpolationPlugin};
+use game_demo::globals::GameParams;
+use game_demo::headless::HeadlessPlugin;
+
+/// Frames at 120 Hz over a 60 Hz simulation moving one unit per tick.
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin { tick_rate: 120.0 })
+        .insert_resource(GameParams {
+            tick_rate: 60.0,
+            ..default()
+        })
+        .add_plugins((FixedStepPlugin, InterpolationPlugin))
+        .add_systems(FixedUpdate, step);
+    app.world_mut()
+        .spawn((Transform::default(), InterpolatedTransform::default()));
+    app
+}
+
+fn step(mut q: Query<&mut Transform, With<InterpolatedTransform>>) {
+    for mut tf in &mut q {
+        tf.translation.x += 1.0;
+    }
+}
+
+fn rendered_x(app: &mut App) -> f32 {
+    let world = app.world_mut();
+    world
+        .query_filtered::<&Transform, With<InterpolatedTransform>>()
+        .single(world)
+        .unwrap()
+        .translation
+        .x
+}
+
+#[test]
+fn ticks_follow_tick_rate() {
+    let mut app = app();
+    for _ in 0..120 {
+        app.update();
+    }
+    let fixed = app.world().resource::<Time<Fixed>>();
+    assert_eq!(fixed.timestep(), Time::<Fixed>::from_hz(60.0).timestep());
+    let ticks = fixed.elapsed().as_secs_f64() / fixed.timestep().as_secs_f64();
+    assert!((ticks - 60.0).abs() <= 1.0, "ran {ticks} ticks");
+}
+
+#[test]
+fn rendered_motion_is_smooth() {
+    let mut app = app();
+    for _ in 0..10 {
+        app.update();
+    }
+    let mut last = rendered_x(&mut app);
+    for _ in 0..20 {
+        app.update();
+        let x = rendered_x(&mut app);
+        assert!((x - last - 0.5).abs() < 1e-3, "jumped from {last} to {x}");
+        last = x;
+    }
+}
+
+#[test]
+fn moving_outside_fixed_update_teleports() {
+    let mut app = app();
+    for _ in 0..10 {
+        app.update();
+    }
+    {
+        let world = app.world_mut();
+        let mut tf = world
+            .query_filtered::<&mut Transform, With<InterpolatedTransform>>()
+            .single_mut(world)
+            .unwrap();
+        tf.translation.x = 100.0;
+    }
+    app.update();
+    let x = rendered_x(&mut app);
+    assert!((100.0..=101.0).contains(&x), "snapped back to {x}");
+}
+
+#[derive(Resource, Default)]
+struct Ticks(u32);
+
+#[test]
+fn headless_apps_tick_once_per_update_at_the_configured_rate() {
+    let missing = std::env::temp_dir().join("game_demo_missing_config.ron");
+    let mut app = App::new();
+    app.add_plugins(ConfigPlugin {
+        args: ["--config", missing.to_str().unwrap(), "--tick-rate", "30"]
+            .map(String::from)
+            .to_vec(),
+    });
+    let tick_rate = app.world().resource::<GameParams>().tick_rate;
+    app.add_plugins(HeadlessPlugin {
+        tick_rate: tick_rate as f64,
+    })
+    .add_plugins(FixedStepPlugin)
+    .init_resource::<Ticks>()
+    .add_systems(FixedUpdate, |mut ticks: ResMut<Ticks>| ticks.0 += 1);
+
+    for _ in 0..60 {
+        app.update();
+    }
+    let ticks = app.world().resource::<Ticks>().0;
+    assert!(ticks.abs_diff(60) <= 1, "ran {ticks} ticks in 60 updates");
+}
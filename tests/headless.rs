@@ -0,0 +1,488 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::{headless_app, run_until_playing, run_until_vehicles};
+use game_demo::globals::{Controlled, InVehicle};
+use game_demo::headless::HeadlessPlugin;
+use game_demo::health::{DamageEvent, DamageStageReached, DamageStages, Health, Wrecked};
+use game_demo::input::Player;
+use game_demo::net_targets::NetTargetsPlugin;
+use game_demo::protocol::{decode, NetMessage};
+use game_demo::relay::RelayServer;
+use game_demo::scoring::Scores;
+use game_demo::shooting_gallery::{ShootingGallery, StartGallery};
+use game_demo::socket_client::{NetMessageReceived, SocketClient};
+use game_demo::targets::Target;
+use game_demo::turrets::{Turret, TurretAim};
+use game_demo::vehicle::{Seats, Vehicle, DRIVER_SEAT};
+use game_demo::vehicle_systems::{RaycastWheel, SuspensionTuning};
+use game_demo::weapon_def::WeaponDef;
+use game_demo::weapons::Projectile;
+
+#[test]
+fn starts_playing_without_a_window() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+
+    let world = app.world_mut();
+    assert_eq!(world.query::<&Target>().iter(world).count(), 4);
+    assert_eq!(
+        world
+            .query_filtered::<&Player, With<Controlled>>()
+            .iter(world)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn level_vehicles_are_built_from_their_definition() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+
+    let world = app.world_mut();
+    let wheels: Vec<usize> = world
+        .query_filtered::<&Children, With<Vehicle>>()
+        .iter(world)
+        .map(|children| {
+            children
+                .iter()
+                .filter(|&c| world.get::<RaycastWheel>(c).is_some())
+                .count()
+        })
+        .collect();
+    assert_eq!(wheels, [4, 4]);
+
+    // Damping is set per chassis, leaving the global tuning alone.
+    let tunings = world
+        .query_filtered::<&SuspensionTuning, With<Vehicle>>()
+        .iter(world)
+        .count();
+    assert_eq!(tunings, 2);
+    assert_eq!(world.resource::<SuspensionTuning>().c, SuspensionTuning::default().c);
+}
+
+#[test]
+fn time_advances_one_tick_per_update() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+
+    let start = app.world().resource::<Time>().elapsed();
+    for _ in 0..60 {
+        app.update();
+    }
+    let elapsed = app.world().resource::<Time>().elapsed() - start;
+    assert_eq!(elapsed, HeadlessPlugin::default().tick() * 60);
+}
+
+#[test]
+fn wrecked_vehicles_eject_their_driver_and_respawn() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .unwrap();
+    let vehicle = world
+        .query_filtered::<Entity, With<Vehicle>>()
+        .iter(world)
+        .next()
+        .unwrap();
+    world.entity_mut(player).remove::<Controlled>().insert(InVehicle { vehicle, seat: 0 });
+    world.entity_mut(vehicle).insert(Controlled);
+    let max = world.get::<Health>(vehicle).unwrap().max;
+    world.send_event(DamageEvent {
+        source: None,
+        target: vehicle,
+        amount: max,
+        point: None,
+        normal: None,
+    });
+
+    // Getting out takes a moment.
+    for _ in 0..30 {
+        app.update();
+    }
+    let world = app.world();
+    assert!(world.get::<Wrecked>(vehicle).is_some());
+    assert_eq!(world.get::<DamageStages>(vehicle).unwrap().reached, 2);
+    assert!(world.get::<Controlled>(player).is_some());
+    assert!(world.get::<InVehicle>(player).is_none());
+
+    let ticks = (5.5 / HeadlessPlugin::default().tick().as_secs_f32()) as usize;
+    for _ in 0..ticks {
+        app.update();
+    }
+    let world = app.world();
+    assert!(world.get::<Wrecked>(vehicle).is_none());
+    assert_eq!(world.get::<Health>(vehicle).unwrap().current, max);
+    assert_eq!(world.get::<DamageStages>(vehicle).unwrap().reached, 0);
+}
+
+#[test]
+fn vehicles_report_each_damage_stage_once() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+
+    let world = app.world_mut();
+    let vehicle = world
+        .query_filtered::<Entity, With<Vehicle>>()
+        .iter(world)
+        .next()
+        .unwrap();
+    let max = world.get::<Health>(vehicle).unwrap().max;
+    let mut cursor = world.resource::<Events<DamageStageReached>>().get_cursor();
+    let mut stages = Vec::new();
+    // Down to a half, a little more, then a fifth of its health.
+    for fraction in [0.5, 0.1, 0.2] {
+        app.world_mut().send_event(DamageEvent {
+            source: None,
+            target: vehicle,
+            amount: max * fraction,
+            point: None,
+            normal: None,
+        });
+        app.update();
+        let events = app.world().resource::<Events<DamageStageReached>>();
+        stages.extend(cursor.read(events).map(|e| (e.target, e.stage)));
+    }
+    assert_eq!(stages, [(vehicle, 1), (vehicle, 2)]);
+}
+
+#[test]
+fn occupants_of_a_despawned_vehicle_get_control_back() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .unwrap();
+    let vehicle = world
+        .query_filtered::<Entity, With<Vehicle>>()
+        .iter(world)
+        .next()
+        .unwrap();
+    world
+        .entity_mut(player)
+        .remove::<Controlled>()
+        .insert((InVehicle { vehicle, seat: DRIVER_SEAT }, Visibility::Hidden));
+    world.entity_mut(vehicle).insert(Controlled);
+    app.update();
+    let seat = app.world().get::<Transform>(player).unwrap().translation;
+
+    app.world_mut().entity_mut(vehicle).despawn();
+    app.update();
+    let world = app.world();
+    assert!(world.get::<InVehicle>(player).is_none());
+    assert!(world.get::<Controlled>(player).is_some());
+    assert_eq!(world.get::<Visibility>(player), Some(&Visibility::Inherited));
+    assert!(world.get::<Transform>(player).unwrap().translation.distance(seat) < 0.5);
+}
+
+/// Presses and releases the enter/exit key.
+fn press_enter_exit(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyE);
+    app.update();
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::KeyE);
+}
+
+#[test]
+fn players_take_the_seat_of_the_nearest_door() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .unwrap();
+    let (vehicle, door) = world
+        .query::<(Entity, &Transform, &Seats)>()
+        .iter(world)
+        .map(|(entity, tf, seats)| (entity, tf.transform_point(seats.0[1].door)))
+        .next()
+        .unwrap();
+
+    // At the passenger door the player rides along and stays in control.
+    world.get_mut::<Transform>(player).unwrap().translation = door;
+    press_enter_exit(&mut app);
+    let world = app.world();
+    assert_eq!(world.get::<InVehicle>(player).unwrap().seat, 1);
+    assert_eq!(world.get::<InVehicle>(player).unwrap().vehicle, vehicle);
+    assert!(world.get::<Controlled>(player).is_some());
+    assert!(world.get::<Controlled>(vehicle).is_none());
+
+    for _ in 0..30 {
+        app.update();
+    }
+    press_enter_exit(&mut app);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(app.world().get::<InVehicle>(player).is_none());
+
+    // At the driver's door they take the wheel.
+    let world = app.world_mut();
+    let door = {
+        let (tf, seats) = world.query::<(&Transform, &Seats)>().get(world, vehicle).unwrap();
+        tf.transform_point(seats.0[DRIVER_SEAT].door)
+    };
+    world.get_mut::<Transform>(player).unwrap().translation = door;
+    press_enter_exit(&mut app);
+    let world = app.world();
+    assert_eq!(world.get::<InVehicle>(player).unwrap().seat, DRIVER_SEAT);
+    assert!(world.get::<Controlled>(player).is_none());
+    assert!(world.get::<Controlled>(vehicle).is_some());
+}
+
+#[test]
+fn drivers_fire_the_vehicle_turrets_instead_of_their_own_weapon() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+
+    let slow = app.world_mut().resource_mut::<Assets<WeaponDef>>().add(WeaponDef {
+        fire_rate: 0.1,
+        ..default()
+    });
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .unwrap();
+    let vehicle = world
+        .query_filtered::<Entity, With<TurretAim>>()
+        .single(world)
+        .unwrap();
+    for mut turret in world.query::<&mut Turret>().iter_mut(world) {
+        turret.weapon = slow.clone();
+    }
+    world
+        .entity_mut(player)
+        .remove::<Controlled>()
+        .insert(InVehicle { vehicle, seat: DRIVER_SEAT });
+    world.entity_mut(vehicle).insert(Controlled);
+    world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+    app.update();
+
+    let world = app.world_mut();
+    let shooters: Vec<Entity> = world
+        .query::<&Projectile>()
+        .iter(world)
+        .map(|p| p.shooter)
+        .collect();
+    assert_eq!(shooters, [vehicle]);
+}
+
+#[test]
+fn target_hits_reduce_health_score_and_destroy_it() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .unwrap();
+    let (target, max) = world
+        .query::<(Entity, &Target, &Health)>()
+        .iter(world)
+        .find(|(_, target, _)| target.id == 0)
+        .map(|(entity, _, health)| (entity, health.max))
+        .unwrap();
+    let hit = |amount| DamageEvent {
+        source: Some(player),
+        target,
+        amount,
+        point: Some(Vec3::ZERO),
+        normal: Some(Vec3::Z),
+    };
+    world.send_event(hit(10.0));
+    app.update();
+    let world = app.world();
+    assert_eq!(world.get::<Health>(target).unwrap().current, max - 10.0);
+    let score = world.resource::<Scores>().get(player);
+    assert_eq!((score.hits, score.damage, score.kills), (1, 10.0, 0));
+
+    app.world_mut().send_event(hit(max));
+    app.update();
+    let world = app.world();
+    assert!(world.get_entity(target).is_err());
+    // Only the hit points that were left count as damage.
+    let score = world.resource::<Scores>().get(player);
+    assert_eq!((score.hits, score.damage, score.kills), (2, max, 1));
+    assert_eq!(world.resource::<ShootingGallery>().score, 10);
+
+    // Targets come back after their respawn delay.
+    let ticks = (5.5 / HeadlessPlugin::default().tick().as_secs_f32()) as usize;
+    for _ in 0..ticks {
+        app.update();
+    }
+    let world = app.world_mut();
+    let health = world
+        .query::<(&Target, &Health)>()
+        .iter(world)
+        .find(|(target, _)| target.id == 0)
+        .map(|(_, health)| *health)
+        .unwrap();
+    assert_eq!(health.current, max);
+}
+
+#[test]
+fn lethal_target_hits_are_broadcast_before_the_target_despawns() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime
+        .block_on(RelayServer::bind("127.0.0.1:0"))
+        .expect("bind relay");
+    let url = format!("ws://{}", server.local_addr().unwrap());
+    runtime.spawn(server.run());
+
+    let mut client = SocketClient::default();
+    let mut peer = SocketClient::default();
+    client.connect(url.clone());
+    peer.connect(url);
+    let mut app = headless_app();
+    app.insert_resource(client)
+        .add_event::<NetMessageReceived>()
+        .add_plugins(NetTargetsPlugin);
+    run_until_playing(&mut app);
+    for _ in 0..500 {
+        if app.world().resource::<SocketClient>().is_connected() && peer.is_connected() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(app.world().resource::<SocketClient>().is_connected());
+    assert!(peer.is_connected());
+
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, With<Player>>()
+        .single(world)
+        .unwrap();
+    let (target, max) = world
+        .query::<(Entity, &Target, &Health)>()
+        .iter(world)
+        .find(|(_, target, _)| target.id == 0)
+        .map(|(entity, _, health)| (entity, health.max))
+        .unwrap();
+    // The second hit lands on an emptied target and takes nothing off.
+    for amount in [max, 10.0] {
+        world.send_event(DamageEvent {
+            source: Some(player),
+            target,
+            amount,
+            point: Some(Vec3::ZERO),
+            normal: None,
+        });
+    }
+    app.update();
+    assert!(app.world().get_entity(target).is_err());
+
+    let mut hits = Vec::new();
+    for _ in 0..50 {
+        hits.extend(
+            std::iter::from_fn(|| peer.try_recv())
+                .filter_map(|frame| decode(&frame).ok())
+                .filter_map(|envelope| match envelope.message {
+                    NetMessage::TargetHit { target, damage, hp, .. } => Some((target, damage, hp)),
+                    _ => None,
+                }),
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(hits, [(0, max.round() as i32, 0)]);
+}
+
+#[test]
+fn hits_on_an_earlier_spawn_of_a_target_are_ignored() {
+    let mut app = headless_app();
+    app.init_resource::<SocketClient>()
+        .add_event::<NetMessageReceived>()
+        .add_plugins(NetTargetsPlugin);
+    run_until_playing(&mut app);
+
+    let world = app.world_mut();
+    let target = world
+        .query::<(Entity, &Target)>()
+        .iter(world)
+        .find(|(_, target)| target.id == 0)
+        .map(|(entity, _)| entity)
+        .unwrap();
+    world.get_mut::<Target>(target).unwrap().generation = 1;
+    let max = world.get::<Health>(target).unwrap().max;
+    let hit = |generation| NetMessageReceived {
+        sender: 7,
+        message: NetMessage::TargetHit {
+            target: 0,
+            generation,
+            point: [0.0; 3],
+            damage: 10,
+            hp: max as i32 - 10,
+        },
+    };
+
+    // Replayed as damage on the next fixed tick.
+    world.send_event(hit(0));
+    app.update();
+    app.update();
+    assert_eq!(app.world().get::<Health>(target).unwrap().current, max);
+
+    app.world_mut().send_event(hit(1));
+    app.update();
+    app.update();
+    assert_eq!(app.world().get::<Health>(target).unwrap().current, max - 10.0);
+}
+
+#[test]
+fn gallery_rounds_start_with_every_target_restored() {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+
+    let world = app.world_mut();
+    let targets: Vec<(Entity, f32)> = world
+        .query_filtered::<(Entity, &Health), With<Target>>()
+        .iter(world)
+        .map(|(entity, health)| (entity, health.max))
+        .collect();
+    let count = targets.len();
+    let [(destroyed, max), (damaged, _), ..] = targets[..] else { panic!("too few targets") };
+    let hit = |target, amount| DamageEvent {
+        source: None,
+        target,
+        amount,
+        point: None,
+        normal: None,
+    };
+    world.send_event(hit(destroyed, max));
+    world.send_event(hit(damaged, 1.0));
+    app.update();
+    assert!(app.world().get_entity(destroyed).is_err());
+
+    app.world_mut().send_event(StartGallery);
+    app.update();
+    app.update();
+    let world = app.world_mut();
+    assert!(world.resource::<ShootingGallery>().is_running());
+    let healths: Vec<Health> = world
+        .query_filtered::<&Health, With<Target>>()
+        .iter(world)
+        .copied()
+        .collect();
+    assert_eq!(healths.len(), count);
+    assert!(healths.iter().all(|health| health.current == health.max));
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use game_demo::health::{impact_damage_for, DamageStages, Health, Impact};
+
+#[test]
+fn damage_reports_depletion_once() {
+    let mut health = Health::new(100.0);
+    assert!(!health.damage(60.0));
+    assert_eq!(health.current, 40.0);
+    assert!(health.damage(60.0));
+    assert_eq!(health.current, 0.0);
+    assert!(!health.damage(10.0));
+
+    health.restore();
+    assert_eq!(health.fraction(), 1.0);
+}
+
+#[test]
+fn impacts_above_the_threshold_deal_damage() {
+    assert_eq!(impact_damage_for(3.0), 0.0);
+    assert!(impact_damage_for(20.0) > 0.0);
+}
+
+#[test]
+fn only_hard_landings_hurt_players() {
+    let mut impact = Impact::default();
+    assert_eq!(impact.update(-20.0, Vec3::ZERO), 0.0);
+    // Still falling.
+    assert_eq!(impact.update(-20.3, Vec3::NEG_Y * 0.3), 0.0);
+    // Hitting the ground stops the fall dead.
+    assert!(impact.update(0.0, Vec3::NEG_Y * 0.6) > 0.0);
+    // Stepping off a kerb is harmless.
+    impact.update(-3.0, Vec3::NEG_Y);
+    assert_eq!(impact.update(0.0, Vec3::NEG_Y * 1.05), 0.0);
+}
+
+#[test]
+fn teleports_are_not_impacts() {
+    let mut impact = Impact::default();
+    impact.update(-30.0, Vec3::new(0.0, -50.0, 0.0));
+    assert_eq!(impact.update(0.0, Vec3::Y * 2.0), 0.0);
+}
+
+#[test]
+fn damage_stages_count_the_thresholds_passed() {
+    let stages = DamageStages::new(vec![0.66, 0.33]);
+    assert_eq!(stages.stage_at(1.0), 0);
+    assert_eq!(stages.stage_at(0.66), 1);
+    assert_eq!(stages.stage_at(0.4), 1);
+    assert_eq!(stages.stage_at(0.0), 2);
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use game_demo::globals::Controlled;
+use game_demo::goals::{Checkpoint, FinishGoal, StartGoal};
+use game_demo::lap_timer::{lap_timer_system, LapCompleted, LapTimer};
+
+/// A course starting at the origin, with one checkpoint at x = 10 and the
+/// finish at x = 20.
+fn course() -> (World, Entity) {
+    let mut world = World::new();
+    let mut time = Time::<()>::default();
+    time.advance_by(Duration::from_secs_f32(1.0 / 60.0));
+    world.insert_resource(time);
+    world.init_resource::<LapTimer>();
+    world.init_resource::<Events<LapCompleted>>();
+    world.spawn((StartGoal, Transform::default()));
+    world.spawn((Checkpoint { index: 0 }, Transform::from_xyz(10.0, 0.0, 0.0)));
+    world.spawn((FinishGoal, Transform::from_xyz(20.0, 0.0, 0.0)));
+    let player = world.spawn((Controlled, Transform::default())).id();
+    (world, player)
+}
+
+/// Moves `player` through `xs` one tick each and returns the laps finished.
+fn drive(world: &mut World, player: Entity, xs: &[f32]) -> usize {
+    let mut cursor = world.resource::<Events<LapCompleted>>().get_cursor_current();
+    let mut laps = 0;
+    for &x in xs {
+        world.get_mut::<Transform>(player).unwrap().translation.x = x;
+        world.run_system_once(lap_timer_system).unwrap();
+        laps += cursor.read(world.resource::<Events<LapCompleted>>()).count();
+    }
+    laps
+}
+
+#[test]
+fn laps_need_their_checkpoints() {
+    let (mut world, player) = course();
+    // Going around the checkpoint doesn't finish the lap.
+    assert_eq!(drive(&mut world, player, &[0.0, 5.0, 15.0, 20.0]), 0);
+    assert_eq!(drive(&mut world, player, &[15.0, 10.0, 15.0, 20.0]), 1);
+    assert!(world.resource::<LapTimer>().last_lap.is_some());
+}
+
+#[test]
+fn a_new_lap_has_to_pass_the_checkpoints_again() {
+    let (mut world, player) = course();
+    assert_eq!(drive(&mut world, player, &[0.0, 10.0, 20.0]), 1);
+    assert_eq!(drive(&mut world, player, &[0.0, 5.0, 20.0]), 0);
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+
+use game_demo::globals::GameParams;
+use game_demo::headless::HeadlessPlugin;
+use game_demo::net_players::{NetPlayersPlugin, RemotePlayer, Snapshot, SnapshotBuffer};
+use game_demo::protocol::NetMessage;
+use game_demo::socket_client::{NetMessageReceived, SocketClient};
+
+fn snap(time: f64, x: f32) -> Snapshot {
+    Snapshot {
+        time,
+        position: Vec3::new(x, 0.0, 0.0),
+        rotation: Quat::IDENTITY,
+        yaw: 0.0,
+        speed: 0.0,
+    }
+}
+
+#[test]
+fn interpolates_between_snapshots() {
+    let mut buf = SnapshotBuffer::default();
+    buf.push(snap(0.0, 0.0));
+    buf.push(snap(1.0, 10.0));
+    let s = buf.sample(0.25, 0.0).unwrap();
+    assert!((s.position.x - 2.5).abs() < 1e-4);
+}
+
+#[test]
+fn out_of_order_snapshots_are_sorted() {
+    let mut buf = SnapshotBuffer::default();
+    buf.push(snap(0.0, 0.0));
+    buf.push(snap(2.0, 20.0));
+    buf.push(snap(1.0, 10.0));
+    assert_eq!(buf.len(), 3);
+    let s = buf.sample(1.5, 0.0).unwrap();
+    assert!((s.position.x - 15.0).abs() < 1e-4);
+}
+
+#[test]
+fn extrapolation_is_clamped() {
+    let mut buf = SnapshotBuffer::default();
+    buf.push(snap(0.0, 0.0));
+    buf.push(snap(1.0, 1.0));
+    let s = buf.sample(1.1, 0.25).unwrap();
+    assert!((s.position.x - 1.1).abs() < 1e-4);
+    let s = buf.sample(5.0, 0.25).unwrap();
+    assert!((s.position.x - 1.25).abs() < 1e-4);
+}
+
+#[test]
+fn empty_buffer_has_no_sample() {
+    assert!(SnapshotBuffer::default().sample(0.0, 1.0).is_none());
+}
+
+#[test]
+fn snapshots_arriving_together_spawn_one_proxy() {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin::default())
+        .insert_resource(GameParams::default())
+        .init_resource::<SocketClient>()
+        .add_event::<NetMessageReceived>()
+        .add_plugins(NetPlayersPlugin);
+    for time in [1.0, 1.1] {
+        app.world_mut().send_event(NetMessageReceived {
+            sender: 7,
+            message: NetMessage::PlayerState {
+                position: [0.0; 3],
+                rotation: [0.0, 0.0, 0.0, 1.0],
+                yaw: 0.0,
+                speed: 0.0,
+                time,
+            },
+        });
+    }
+    app.update();
+
+    let world = app.world_mut();
+    let proxies: Vec<usize> = world
+        .query::<&RemotePlayer>()
+        .iter(world)
+        .map(|proxy| proxy.snapshots.len())
+        .collect();
+    assert_eq!(proxies, [2]);
+}
+
+#[test]
+fn proxies_share_their_mesh_and_a_material_per_hue() {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugin::default())
+        .insert_resource(GameParams::default())
+        .init_resource::<SocketClient>()
+        .add_event::<NetMessageReceived>()
+        .add_plugins(NetPlayersPlugin);
+    // 7 and 367 get the same hue.
+    for sender in [7, 8, 367] {
+        app.world_mut().send_event(NetMessageReceived {
+            sender,
+            message: NetMessage::PlayerState {
+                position: [0.0; 3],
+                rotation: [0.0, 0.0, 0.0, 1.0],
+                yaw: 0.0,
+                speed: 0.0,
+                time: 1.0,
+            },
+        });
+    }
+    app.update();
+
+    let world = app.world_mut();
+    let mut proxies: Vec<_> = world
+        .query::<(&RemotePlayer, &Mesh3d, &MeshMaterial3d<StandardMaterial>)>()
+        .iter(world)
+        .map(|(proxy, mesh, material)| (proxy.client_id, mesh.0.id(), material.0.id()))
+        .collect();
+    proxies.sort_by_key(|(id, _, _)| *id);
+    let [(_, mesh, seven), (_, _, eight), (_, _, other_seven)] = proxies[..] else {
+        panic!("expected three proxies, got {proxies:?}");
+    };
+    assert!(proxies.iter().all(|(_, m, _)| *m == mesh));
+    assert_eq!(seven, other_seven);
+    assert_ne!(seven, eight);
+}
@@ -1,19 +1,23 @@
 use bevy::prelude::*;
 use avian3d::prelude::*;
 
-use game_demo::input::{player_controller, Player};
-use game_demo::globals::GameParams;
+use game_demo::actions::ActionsPlugin;
+use game_demo::game_state::{GameState, GameStatePlugin};
+use game_demo::headless::HeadlessPlugin;
+use game_demo::input::{Player, PlayerControlPlugin};
+use game_demo::globals::{Controlled, GameParams};
 
 #[test]
 fn player_falls_onto_ground() {
     let mut app = App::new();
-    app.add_plugins((MinimalPlugins, PhysicsPlugins::default()));
+    app.add_plugins((HeadlessPlugin::default(), PhysicsPlugins::default()));
     app.insert_resource(Gravity(Vec3::new(0.0, -9.81, 0.0)));
     app.insert_resource(GameParams::default());
-    app.add_systems(Update, player_controller);
+    app.add_plugins((ActionsPlugin, GameStatePlugin, PlayerControlPlugin));
+    app.insert_state(GameState::Playing);
 
     // simple ground collider
-    app.world.spawn((
+    app.world_mut().spawn((
         Collider::cuboid(5.0, 0.5, 5.0),
         Transform::from_xyz(0.0, -0.5, 0.0),
         GlobalTransform::default(),
@@ -21,7 +25,7 @@ fn player_falls_onto_ground() {
     ));
 
     // player above the ground
-    app.world.spawn((
+    app.world_mut().spawn((
         Collider::cuboid(0.5, 0.5, 0.5),
         Transform::from_xyz(0.0, 5.0, 0.0),
         GlobalTransform::default(),
@@ -31,6 +35,7 @@ fn player_falls_onto_ground() {
             half_extents: Vec3::splat(0.5),
             ..Default::default()
         },
+        Controlled,
     ));
 
     // simulate several frames
@@ -38,7 +43,8 @@ fn player_falls_onto_ground() {
         app.update();
     }
 
-    let (tf, player) = app.world.query::<(&Transform, &Player)>().single(&app.world);
+    let world = app.world_mut();
+    let (tf, player) = world.query::<(&Transform, &Player)>().single(world).unwrap();
     assert!(tf.translation.y >= 0.0, "player fell through the ground: {}", tf.translation.y);
     assert!(player.grounded, "player should be grounded after falling");
 }
@@ -0,0 +1,78 @@
+use game_demo::protocol::*;
+
+#[test]
+fn envelope_round_trip() {
+    let envelope = Envelope::new(
+        42,
+        NetMessage::PlayerState {
+            position: [1.0, 2.0, 3.0],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            yaw: 0.5,
+            speed: 12.0,
+            time: 3.25,
+        },
+    );
+    let frame = encode(&envelope);
+    assert!(frame.contains("\"action\":\"sendMessage\""));
+    assert_eq!(decode(&frame).unwrap(), envelope);
+}
+
+#[test]
+fn unknown_message_type_is_tolerated() {
+    let frame = r#"{"action":"sendMessage","data":{"version":1,"sender":7,"message":{"type":"teleport","x":1}}}"#;
+    let envelope = decode(frame).unwrap();
+    assert_eq!(envelope.sender, 7);
+    assert_eq!(envelope.message, NetMessage::Unknown);
+}
+
+#[test]
+fn plain_text_decodes_as_anonymous_chat() {
+    let frame = r#"{"action":"sendMessage","data":"hello there"}"#;
+    let envelope = decode(frame).unwrap();
+    assert_eq!(envelope.sender, ANONYMOUS_CLIENT);
+    assert_eq!(
+        envelope.message,
+        NetMessage::Chat { text: "hello there".to_string() }
+    );
+}
+
+#[test]
+fn stringified_envelope_is_accepted() {
+    let inner = r#"{"version":1,"sender":3,"message":{"type":"leave"}}"#;
+    let frame = serde_json::json!({ "action": "sendMessage", "data": inner }).to_string();
+    assert_eq!(decode(&frame).unwrap().message, NetMessage::Leave);
+}
+
+#[test]
+fn old_versions_are_rejected() {
+    let frame = r#"{"data":{"version":0,"sender":1,"message":{"type":"leave"}}}"#;
+    assert_eq!(decode(frame), Err(DecodeError::UnsupportedVersion(0)));
+}
+
+#[test]
+fn garbage_is_malformed() {
+    assert!(matches!(decode("not json"), Err(DecodeError::Malformed(_))));
+    assert_eq!(decode("{}"), Err(DecodeError::MissingData));
+}
+
+#[test]
+fn version_negotiation_picks_highest_common() {
+    assert_eq!(negotiate_version(1, 99), Some(PROTOCOL_VERSION));
+    assert_eq!(negotiate_version(MIN_PROTOCOL_VERSION, MIN_PROTOCOL_VERSION), Some(MIN_PROTOCOL_VERSION));
+    assert_eq!(negotiate_version(PROTOCOL_VERSION + 1, PROTOCOL_VERSION + 5), None);
+}
+
+#[test]
+fn target_hits_without_a_generation_hit_the_first_spawn() {
+    let frame = r#"{"data":{"version":1,"sender":1,"message":{"type":"target_hit","target":2,"point":[0.0,1.0,0.0],"damage":10,"hp":40}}}"#;
+    assert_eq!(
+        decode(frame).unwrap().message,
+        NetMessage::TargetHit {
+            target: 2,
+            generation: 0,
+            point: [0.0, 1.0, 0.0],
+            damage: 10,
+            hp: 40,
+        }
+    );
+}
@@ -0,0 +1,80 @@
+use std::time::{Duration, Instant};
+
+use game_demo::protocol::{decode, NetMessage};
+use game_demo::relay::RelayServer;
+use game_demo::socket_client::SocketClient;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn wait_until(mut cond: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < TIMEOUT {
+        if cond() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn relay_broadcasts_between_clients() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime
+        .block_on(RelayServer::bind("127.0.0.1:0"))
+        .expect("bind relay");
+    let url = format!("ws://{}", server.local_addr().unwrap());
+    runtime.spawn(server.run());
+
+    let mut alice = SocketClient::default();
+    let mut bob = SocketClient::default();
+    alice.connect(url.clone());
+    bob.connect(url);
+    assert!(wait_until(|| alice.is_connected() && bob.is_connected()));
+
+    alice.send("hello bob".to_string());
+
+    let mut received = None;
+    assert!(wait_until(|| {
+        while let Some(frame) = bob.try_recv() {
+            if let Ok(envelope) = decode(&frame) {
+                if envelope.sender == alice.client_id() {
+                    received = Some(envelope.message);
+                    return true;
+                }
+            }
+        }
+        false
+    }));
+    assert_eq!(
+        received,
+        Some(NetMessage::Chat { text: "hello bob".to_string() })
+    );
+
+    // The sender receives its own broadcast too, like the AWS backend.
+    assert!(wait_until(|| alice.try_recv().is_some()));
+}
+
+#[test]
+fn messages_queued_before_connect_are_delivered() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime
+        .block_on(RelayServer::bind("127.0.0.1:0"))
+        .expect("bind relay");
+    let url = format!("ws://{}", server.local_addr().unwrap());
+    runtime.spawn(server.run());
+
+    let mut listener = SocketClient::default();
+    listener.connect(url.clone());
+    assert!(wait_until(|| listener.is_connected()));
+
+    let mut early = SocketClient::default();
+    early.connect(url);
+    early.send("queued".to_string());
+
+    assert!(wait_until(|| {
+        std::iter::from_fn(|| listener.try_recv())
+            .filter_map(|frame| decode(&frame).ok())
+            .any(|e| e.message == NetMessage::Chat { text: "queued".to_string() })
+    }));
+}
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use game_demo::socket_client::backoff_delay;
+
+#[test]
+fn backoff_grows_exponentially_with_jitter() {
+    for _ in 0..50 {
+        let first = backoff_delay(1);
+        assert!(first >= Duration::from_millis(250) && first <= Duration::from_millis(500));
+        let fourth = backoff_delay(4);
+        assert!(fourth >= Duration::from_millis(2000) && fourth <= Duration::from_millis(4000));
+    }
+}
+
+#[test]
+fn backoff_is_capped() {
+    for attempt in [10, 20, 1000] {
+        assert!(backoff_delay(attempt) <= Duration::from_secs(30));
+        assert!(backoff_delay(attempt) >= Duration::from_secs(15));
+    }
+}
@@ -0,0 +1,58 @@
+use std::fs;
+
+use game_demo::level::{LevelDef, TargetKind};
+use game_demo::shooting_gallery::ShootingGallery;
+use game_demo::targets::PopUp;
+
+#[test]
+fn default_level_has_every_target_kind() {
+    let text = fs::read_to_string("assets/levels/default.level.ron").unwrap();
+    let level: LevelDef = ron::from_str(&text).unwrap();
+    let kinds: Vec<_> = level.targets.iter().map(|t| &t.kind).collect();
+    assert!(matches!(kinds[..], [
+        TargetKind::Static,
+        TargetKind::Patrol { .. },
+        TargetKind::PopUp { .. },
+        TargetKind::Armoured { .. },
+    ]));
+    // Unlisted fields take their defaults.
+    assert_eq!(level.targets[0].points, 10);
+    assert_eq!(level.targets[0].respawn, Some(5.0));
+    let TargetKind::Armoured { weak_points, .. } = &level.targets[3].kind else { unreachable!() };
+    assert_eq!(weak_points[0].multiplier, 3.0);
+    assert_eq!(weak_points[1].multiplier, 5.0);
+}
+
+#[test]
+fn pop_up_targets_alternate_between_up_and_down() {
+    let mut pop_up = PopUp::new(1.0, 2.0, 0.5);
+    assert_eq!(pop_up.update(0.5), 0.5);
+    assert!(pop_up.update(0.6) < 0.5);
+    assert!(!pop_up.raised);
+    assert!(pop_up.update(1.5) < 0.5);
+    assert_eq!(pop_up.update(0.6), 0.5);
+    assert!(pop_up.raised);
+}
+
+#[test]
+fn gallery_rounds_score_until_time_runs_out() {
+    let mut gallery = ShootingGallery::default();
+    gallery.award(10);
+    assert_eq!(gallery.score, 10);
+
+    gallery.start(2.0);
+    assert_eq!(gallery.score, 0);
+    gallery.award(25);
+    assert_eq!(gallery.update(1.0), None);
+    assert_eq!(gallery.update(1.5), Some(25));
+    assert!(!gallery.is_running());
+    assert_eq!(gallery.best, 25);
+
+    // The final score stands until the next round.
+    gallery.award(50);
+    assert_eq!(gallery.score, 25);
+    gallery.start(2.0);
+    gallery.award(5);
+    gallery.update(3.0);
+    assert_eq!(gallery.best, 25);
+}
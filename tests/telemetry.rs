@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use game_demo::telemetry::{Telemetry, TelemetryError, TelemetrySample, WheelSample};
+
+fn sample(time: f32) -> TelemetrySample {
+    TelemetrySample {
+        time,
+        speed: 10.0,
+        wheels: vec![
+            WheelSample {
+                compression: 0.1,
+                grounded: true,
+                ..default()
+            };
+            4
+        ],
+        ..default()
+    }
+}
+
+#[test]
+fn oldest_samples_are_dropped() {
+    let vehicle = Entity::from_raw(1);
+    let mut telemetry = Telemetry::with_capacity(3);
+    for i in 0..5 {
+        telemetry.record(vehicle, sample(i as f32));
+    }
+    let times: Vec<f32> = telemetry.samples().iter().map(|s| s.time).collect();
+    assert_eq!(times, [2.0, 3.0, 4.0]);
+
+    // Another vehicle starts a new recording.
+    telemetry.record(Entity::from_raw(2), sample(5.0));
+    assert_eq!(telemetry.samples().len(), 1);
+}
+
+#[test]
+fn csv_has_a_column_per_value() {
+    let mut telemetry = Telemetry::default();
+    telemetry.record(Entity::from_raw(1), sample(0.0));
+    telemetry.record(Entity::from_raw(1), sample(0.5));
+
+    let csv = telemetry.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    let header: Vec<&str> = lines[0].split(',').collect();
+    assert_eq!(header.len(), 14 + 4 * 11);
+    assert!(header.contains(&"w3_lateral_force"));
+    assert!(lines.iter().all(|l| l.split(',').count() == header.len()));
+    assert!(lines[2].starts_with("0.5,10,"));
+}
+
+#[test]
+fn json_lists_the_samples() {
+    let mut telemetry = Telemetry::default();
+    telemetry.record(Entity::from_raw(1), sample(0.0));
+    let json: serde_json::Value = serde_json::from_str(&telemetry.to_json().unwrap()).unwrap();
+    assert_eq!(json[0]["wheels"][0]["compression"], 0.1);
+    assert_eq!(json[0]["wheels"].as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn recording_starts_off_and_export_errors_are_telemetrys_own() {
+    let telemetry = Telemetry::default();
+    assert!(!telemetry.recording);
+
+    let file = std::env::temp_dir().join("telemetry-not-a-dir");
+    std::fs::write(&file, "").unwrap();
+    let err = telemetry.export(&file.join("out.csv")).unwrap_err();
+    assert!(matches!(err, TelemetryError::Io(_)));
+}
@@ -0,0 +1,52 @@
+use game_demo::tire::{slip_angle, slip_ratio, tire_force, MagicFormula};
+
+const LOAD: f32 = 4000.0;
+
+#[test]
+fn slip_ratio_spans_locked_to_rolling() {
+    assert_eq!(slip_ratio(10.0, 10.0), 0.0);
+    assert_eq!(slip_ratio(0.0, 10.0), -1.0);
+    assert!(slip_ratio(20.0, 10.0) > 0.0);
+    // Stays bounded when starting from rest.
+    assert!(slip_ratio(0.5, 0.0).abs() <= 1.0);
+}
+
+#[test]
+fn slip_angle_follows_sideways_motion() {
+    assert_eq!(slip_angle(10.0, 0.0), 0.0);
+    assert!((slip_angle(10.0, 10.0) - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+    assert_eq!(slip_angle(-10.0, 1.0), slip_angle(10.0, 1.0));
+}
+
+#[test]
+fn magic_formula_peaks_at_peak_coefficient() {
+    let curve = MagicFormula::LONGITUDINAL;
+    let peak = (0..100)
+        .map(|i| curve.force(i as f32 * 0.01, LOAD))
+        .fold(0.0, f32::max);
+    assert!((peak - LOAD).abs() < LOAD * 0.01, "peak {peak}");
+    assert_eq!(curve.force(-0.1, LOAD), -curve.force(0.1, LOAD));
+    // Past the peak the tire slides with less grip.
+    assert!(curve.force(1.0, LOAD) < peak);
+}
+
+#[test]
+fn pure_slip_forces_use_their_own_coefficient() {
+    let drive = tire_force(0.1, 0.0, LOAD, 1.2, 0.8);
+    assert!(drive.longitudinal > 0.0);
+    assert!(drive.longitudinal <= 1.2 * LOAD);
+    assert_eq!(drive.lateral, 0.0);
+
+    let corner = tire_force(0.0, 0.1, LOAD, 1.2, 0.8);
+    assert!(corner.lateral < 0.0, "lateral force opposes the slide");
+    assert!(-corner.lateral <= 0.8 * LOAD);
+}
+
+#[test]
+fn combined_slip_stays_inside_friction_circle() {
+    let mu = 1.0;
+    let force = tire_force(0.12, 0.15, LOAD, mu, mu);
+    let total = force.longitudinal.hypot(force.lateral);
+    assert!(total <= mu * LOAD * 1.0001, "total {total}");
+    assert!(force.longitudinal < tire_force(0.12, 0.0, LOAD, mu, mu).longitudinal);
+}
@@ -0,0 +1,94 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::fs;
+
+use bevy::prelude::*;
+
+use game_demo::turrets::{Turret, TurretAim};
+use game_demo::vehicle_def::{HardpointDef, VehicleDef};
+use game_demo::weapon_def::WeaponDef;
+
+fn turret(yaw_limit: f32) -> Turret {
+    Turret::new(
+        &HardpointDef {
+            position: [0.0; 3],
+            weapon: String::new(),
+            yaw_limit,
+            pitch_limits: [-0.2, 0.6],
+            turn_rate: 2.0,
+            cooldown: 0.25,
+        },
+        Handle::default(),
+    )
+}
+
+#[test]
+fn turrets_turn_at_their_rate_and_stop_at_their_limits() {
+    let mut limited = turret(1.0);
+    limited.track(0.5, 0.0, 0.1);
+    assert!((limited.yaw - 0.2).abs() < 1e-5);
+    for _ in 0..100 {
+        limited.track(2.5, 1.5, 0.1);
+    }
+    assert!((limited.yaw - 1.0).abs() < 1e-5);
+    assert!((limited.pitch - 0.6).abs() < 1e-5);
+
+    // Aiming behind a limited turret doesn't swing it through the back.
+    limited.track(-2.5, 0.0, 0.1);
+    assert!(limited.yaw < 1.0 && limited.yaw > 0.0);
+}
+
+#[test]
+fn free_turrets_take_the_short_way_round() {
+    let mut free = turret(PI);
+    free.yaw = 3.0;
+    free.track(-3.0, 0.0, 0.1);
+    // Through pi rather than back through 0.
+    assert!(free.yaw.abs() > 3.0);
+    for _ in 0..10 {
+        free.track(-3.0, 0.0, 0.1);
+    }
+    assert!((free.yaw + 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn cooldown_holds_back_fast_weapons() {
+    let turret = turret(PI);
+    let fast = WeaponDef {
+        fire_rate: 50.0,
+        ..default()
+    };
+    assert_eq!(turret.fire_interval(&fast), 0.25);
+    let slow = WeaponDef {
+        fire_rate: 2.0,
+        ..default()
+    };
+    assert_eq!(turret.fire_interval(&slow), 0.5);
+}
+
+#[test]
+fn aim_is_held_in_world_space() {
+    let aim = TurretAim {
+        yaw: FRAC_PI_2,
+        pitch: 0.3,
+    };
+    assert!(aim.direction().x > 0.9);
+    let (yaw, pitch) = aim.relative_to(Quat::from_rotation_y(FRAC_PI_2));
+    assert!(yaw.abs() < 1e-5);
+    assert!((pitch - 0.3).abs() < 1e-5);
+    let (yaw, _) = aim.relative_to(Quat::IDENTITY);
+    assert!((yaw - FRAC_PI_2).abs() < 1e-5);
+}
+
+#[test]
+fn rally_car_carries_a_turret() {
+    let text = fs::read_to_string("assets/vehicles/rally.vehicle.ron").unwrap();
+    let def: VehicleDef = ron::from_str(&text).unwrap();
+    assert_eq!(def.hardpoints.len(), 1);
+    assert!(fs::metadata(format!("assets/{}", def.hardpoints[0].weapon)).is_ok());
+    assert_eq!(def.hardpoints[0].yaw_limit, PI);
+    assert_eq!(def.hardpoints[0].cooldown, 0.25);
+
+    let text = fs::read_to_string("assets/vehicles/car.vehicle.ron").unwrap();
+    let def: VehicleDef = ron::from_str(&text).unwrap();
+    assert!(def.hardpoints.is_empty());
+}
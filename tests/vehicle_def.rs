@@ -0,0 +1,48 @@
+use std::fs;
+
+use game_demo::drivetrain::{Differential, DriveLayout};
+use game_demo::vehicle_def::{VehicleDef, DEFAULT_VEHICLE};
+
+fn load(path: &str) -> VehicleDef {
+    let text = fs::read_to_string(format!("assets/{path}")).unwrap();
+    ron::from_str(&text).unwrap()
+}
+
+#[test]
+fn shipped_vehicles_parse() {
+    let entries = fs::read_dir("assets/vehicles").unwrap();
+    let mut count = 0;
+    for entry in entries {
+        let path = entry.unwrap().path();
+        let text = fs::read_to_string(&path).unwrap();
+        let def: VehicleDef =
+            ron::from_str(&text).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        assert_eq!(def.wheels.len(), 4, "{}", def.name);
+        assert!(!def.seats.is_empty(), "{}", def.name);
+        count += 1;
+    }
+    assert!(count >= 2);
+}
+
+#[test]
+fn omitted_fields_take_defaults() {
+    let car = load(DEFAULT_VEHICLE);
+    assert!(car.suspension.is_none());
+    assert!(car.center_of_mass.is_none());
+    assert_eq!(car.drivetrain.layout, DriveLayout::Rwd);
+    assert_eq!(car.drivetrain.gear, 0);
+    assert!(car.wheels.iter().all(|w| w.width == 0.3));
+    assert_eq!(car.damage_stages, [0.66, 0.33]);
+}
+
+#[test]
+fn vehicles_carry_their_own_tuning() {
+    let rally = load("vehicles/rally.vehicle.ron");
+    let suspension = rally.suspension.unwrap();
+    assert_eq!(suspension.mu_long, 1.2);
+    // Unlisted suspension fields fall back to the defaults.
+    assert!(!suspension.gizmo);
+    assert_eq!(rally.drivetrain.layout, DriveLayout::Awd);
+    assert_eq!(rally.drivetrain.differential, Differential::LimitedSlip { locking: 0.6 });
+    assert_eq!(rally.drivetrain.top_gear(), 6);
+}
@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+
+use game_demo::vehicle::{Seat, Seats};
+
+fn four_seats() -> Seats {
+    Seats(vec![
+        Seat { position: Vec3::new(-0.4, 0.2, 0.2), door: Vec3::new(-1.6, -0.5, 0.2) },
+        Seat { position: Vec3::new(0.4, 0.2, 0.2), door: Vec3::new(1.6, -0.5, 0.2) },
+        Seat { position: Vec3::new(-0.4, 0.2, -0.7), door: Vec3::new(-1.6, -0.5, -0.7) },
+        Seat { position: Vec3::new(0.4, 0.2, -0.7), door: Vec3::new(1.6, -0.5, -0.7) },
+    ])
+}
+
+#[test]
+fn exits_prefer_the_own_door_then_the_nearest_others() {
+    let seats = four_seats();
+    let candidates = seats.exit_candidates(0);
+    assert_eq!(candidates.len(), 5);
+    assert_eq!(candidates[0], seats.0[0].door);
+    // The rear door on the same side is closer than the other side.
+    assert_eq!(candidates[1], seats.0[2].door);
+    assert_eq!(candidates[4].y, seats.0[0].position.y + 1.5);
+}
+
+#[test]
+fn shared_doors_are_tried_once() {
+    let door = Vec3::new(-1.5, -0.5, 0.0);
+    let seats = Seats(vec![
+        Seat { position: Vec3::ZERO, door },
+        Seat { position: Vec3::Z, door },
+    ]);
+    assert_eq!(seats.exit_candidates(1), [door, Vec3::new(0.0, 1.5, 1.0)]);
+    // Unknown seats still get out through the roof.
+    assert_eq!(seats.exit_candidates(5).len(), 1);
+}
@@ -1,58 +1,144 @@
-use bevy::prelude::*;
+mod common;
+
+use std::time::Duration;
+
 use avian3d::prelude::*;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
 
+use common::{headless_app, run_until_playing, run_until_vehicles};
+use game_demo::brakes::{apply_brakes, Brakes};
+use game_demo::globals::GameParams;
+use game_demo::vehicle::Vehicle;
 use game_demo::vehicle_systems::*;
-use game_demo::vehicle::VehiclePlugin;
-use game_demo::world::WorldPlugin;
-
-fn setup_app() -> App {
-    let mut app = App::new();
-    app.add_plugins((MinimalPlugins, PhysicsPlugins::default()));
-    app.insert_resource(Gravity(Vec3::new(0.0, -9.81, 0.0)));
-    app.insert_resource(SuspensionTuning::default());
-    app.add_plugins(WorldPlugin);
-    app.add_plugins(VehiclePlugin);
-    app.add_systems(Update, (
-        raycast_wheels,
-        apply_suspension.after(raycast_wheels),
-        compute_tire_forces.after(apply_suspension),
-        apply_anti_roll.after(compute_tire_forces),
-    ));
-    app
+
+const SPEED: f32 = 20.0;
+const WHEEL_RADIUS: f32 = 0.5;
+
+/// A car at the origin rolling forward at [`SPEED`], its wheels at
+/// (±1, -0.5, ±1.5) on the ground with the given compressions, front-left
+/// first. The contact points are below the centre of mass, so tire forces
+/// pitch and roll the body.
+fn rolling_car(compression: [f32; 4]) -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(SuspensionTuning::default());
+    world.insert_resource(GameParams::default());
+    let mut time = Time::<()>::default();
+    time.advance_by(Duration::from_secs_f32(1.0 / 60.0));
+    world.insert_resource(time);
+
+    let car = world
+        .spawn((
+            Transform::default(),
+            GlobalTransform::default(),
+            LinearVelocity(Vec3::Z * SPEED),
+            AngularVelocity::ZERO,
+            ExternalForce::new(Vec3::ZERO).with_persistence(false),
+            Chassis { mass: 800.0 },
+            Vehicle::default(),
+            Brakes::default(),
+        ))
+        .id();
+    let mounts = [
+        Vec3::new(-1.0, -0.5, 1.5),
+        Vec3::new(1.0, -0.5, 1.5),
+        Vec3::new(-1.0, -0.5, -1.5),
+        Vec3::new(1.0, -0.5, -1.5),
+    ];
+    for (mount, compression) in mounts.into_iter().zip(compression) {
+        let mut wheel = RaycastWheel::new(mount, WHEEL_RADIUS, mount.z > 0.0, mount.x < 0.0, false);
+        wheel.grounded = true;
+        wheel.compression = compression;
+        wheel.prev_compression = compression;
+        wheel.contact_point = mount - Vec3::Y * WHEEL_RADIUS;
+        wheel.angular_velocity = SPEED / WHEEL_RADIUS;
+        let wheel = world.spawn(wheel).id();
+        world.entity_mut(car).add_child(wheel);
+    }
+    (world, car)
+}
+
+/// Runs one tick of the suspension and tire systems and returns the torque
+/// they put on the chassis.
+fn tick(world: &mut World, car: Entity) -> Vec3 {
+    world.run_system_once(apply_suspension).unwrap();
+    world.run_system_once(apply_anti_roll).unwrap();
+    world.run_system_once(apply_brakes).unwrap();
+    world.run_system_once(compute_tire_forces).unwrap();
+    world.get::<ExternalForce>(car).unwrap().torque()
 }
 
 #[test]
 fn flat_ground_idle() {
-    let mut app = setup_app();
-    for _ in 0..120 { app.update(); }
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+    run_until_vehicles(&mut app);
+    for _ in 0..120 {
+        app.update();
+    }
     // After settling the chassis should remain roughly at rest.
-    let lv = app.world.query::<&LinearVelocity>().single(&app.world);
-    assert!(lv.0.length() < 0.1);
+    let world = app.world_mut();
+    for lv in world.query_filtered::<&LinearVelocity, With<Vehicle>>().iter(world) {
+        assert!(lv.0.length() < 0.1, "chassis still moving at {}", lv.0);
+    }
 }
 
 #[test]
 fn one_wheel_bump() {
-    let mut app = setup_app();
-    // simulate a short run over a bump under front-left wheel
-    for _ in 0..180 { app.update(); }
-    let rot = app.world.query::<&Transform>().single(&app.world);
-    assert!(rot.rotation.to_euler(EulerRot::XYZ).0.abs() <= 0.087);
+    // The front-left wheel rides up a bump.
+    let (mut world, car) = rolling_car([0.2, 0.1, 0.1, 0.1]);
+    let torque = tick(&mut world, car);
+    // Positive rotation about X lowers the front, about Z lowers the left
+    // (-X) side: the bump lifts its own corner.
+    assert!(torque.x < 0.0, "{torque}");
+    assert!(torque.z < 0.0, "{torque}");
 }
 
 #[test]
 fn jump_and_land() {
-    let mut app = setup_app();
-    // drop from 1m height
-    for _ in 0..240 { app.update(); }
-    let wheel = app.world.query::<&RaycastWheel>().single(&app.world);
-    assert!(wheel.compression <= 0.75 * (SuspensionTuning::default().max_travel));
+    // In the air no wheel pushes.
+    let (mut world, car) = rolling_car([0.1; 4]);
+    for mut wheel in world.query::<&mut RaycastWheel>().iter_mut(&mut world) {
+        wheel.grounded = false;
+    }
+    tick(&mut world, car);
+    assert_eq!(world.get::<ExternalForce>(car).unwrap().force(), Vec3::ZERO);
+
+    // Landing level compresses all four at once: the dampers push harder
+    // than the springs alone, without tipping the body.
+    let (mut world, car) = rolling_car([0.1; 4]);
+    for mut wheel in world.query::<&mut RaycastWheel>().iter_mut(&mut world) {
+        wheel.prev_compression = 0.0;
+    }
+    world.run_system_once(apply_suspension).unwrap();
+    let force = *world.get::<ExternalForce>(car).unwrap();
+    assert!(force.force().y > 4.0 * SuspensionTuning::default().k * 0.1);
+    assert!(force.torque().length() < 1e-2, "{}", force.torque());
 }
 
 #[test]
-fn high_speed_steer() {
-    let mut app = setup_app();
-    // accelerate to 20m/s then steer
-    for _ in 0..120 { app.update(); }
-    let av = app.world.query::<&AngularVelocity>().single(&app.world);
-    assert!(av.0.length() < 0.5);
+fn braking_pitches_the_nose_down() {
+    let (mut world, car) = rolling_car([0.1; 4]);
+    world.get_mut::<Vehicle>(car).unwrap().brake = 1.0;
+    let torque = tick(&mut world, car);
+    assert!(torque.x > 0.0, "{torque}");
+}
+
+#[test]
+fn cornering_rolls_the_body_to_the_outside() {
+    let (mut world, car) = rolling_car([0.1; 4]);
+    world.get_mut::<Vehicle>(car).unwrap().steer = 1.0;
+    let torque = tick(&mut world, car);
+
+    // The car turns towards the side its front wheels point to and leans
+    // away from it.
+    let inside = world
+        .query::<&RaycastWheel>()
+        .iter(&world)
+        .find(|wheel| wheel.is_front)
+        .map(|wheel| (Quat::from_rotation_y(wheel.steer_angle) * Vec3::Z).x)
+        .unwrap();
+    assert!(inside != 0.0);
+    // A torque about Z lowers the +X side when negative.
+    assert!(torque.z * inside > 0.0, "{torque}");
 }
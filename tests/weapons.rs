@@ -0,0 +1,134 @@
+mod common;
+
+use std::fs;
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use common::{headless_app, run_until_playing};
+use game_demo::globals::Controlled;
+use game_demo::input::Player;
+use game_demo::weapon_def::{FireMode, WeaponDef};
+use game_demo::weapons::{scatter, Inventory, Projectile, Tracer};
+
+#[test]
+fn shipped_weapons_parse() {
+    let mut names = Vec::new();
+    for entry in fs::read_dir("assets/weapons").unwrap() {
+        let path = entry.unwrap().path();
+        let text = fs::read_to_string(&path).unwrap();
+        let def: WeaponDef =
+            ron::from_str(&text).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        names.push(def.name);
+    }
+    names.sort();
+    assert_eq!(names, ["Laser", "Rail gun", "Rocket", "Shotgun"]);
+
+    let rail: WeaponDef =
+        ron::from_str(&fs::read_to_string("assets/weapons/railgun.weapon.ron").unwrap()).unwrap();
+    assert_eq!(rail.mode, FireMode::Hitscan);
+    // Unlisted fields keep the laser's values.
+    assert_eq!(rail.pellets, 1);
+    assert_eq!(rail.speed, WeaponDef::default().speed);
+}
+
+#[test]
+fn inventory_wraps_around() {
+    let mut inventory = Inventory::new(vec![Handle::default(); 3]);
+    inventory.previous();
+    assert_eq!(inventory.selected, 2);
+    inventory.next();
+    assert_eq!(inventory.selected, 0);
+
+    let mut empty = Inventory::default();
+    empty.next();
+    empty.previous();
+    assert!(empty.current().is_none());
+}
+
+#[test]
+fn scatter_stays_inside_the_cone() {
+    let mut rng = StdRng::seed_from_u64(7);
+    assert_eq!(scatter(Vec3::Z, 0.0, &mut rng), Vec3::Z);
+    for _ in 0..100 {
+        let dir = scatter(Vec3::Z, 0.1, &mut rng);
+        assert!((dir.length() - 1.0).abs() < 1e-5);
+        assert!(dir.angle_between(Vec3::Z) <= 0.1 + 1e-5);
+    }
+}
+
+/// Runs a headless game with the player holding only `def` and the fire
+/// button down for one tick.
+fn fire_once(def: WeaponDef) -> App {
+    let mut app = headless_app();
+    run_until_playing(&mut app);
+
+    let handle = app.world_mut().resource_mut::<Assets<WeaponDef>>().add(def);
+    let world = app.world_mut();
+    let player = world
+        .query_filtered::<Entity, (With<Player>, With<Controlled>)>()
+        .single(world)
+        .unwrap();
+    world.entity_mut(player).insert(Inventory::new(vec![handle]));
+    world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+    app.update();
+    app
+}
+
+fn count<C: Component>(app: &mut App) -> usize {
+    let world = app.world_mut();
+    world.query::<&C>().iter(world).count()
+}
+
+#[test]
+fn pellets_spawn_one_projectile_each() {
+    let mut app = fire_once(WeaponDef {
+        pellets: 5,
+        spread: 0.1,
+        ..default()
+    });
+    assert_eq!(count::<Projectile>(&mut app), 5);
+    assert_eq!(count::<Tracer>(&mut app), 0);
+
+    // Shots share their weapon's mesh and material.
+    let world = app.world_mut();
+    let mut assets: Vec<(AssetId<Mesh>, AssetId<StandardMaterial>)> = world
+        .query_filtered::<(&Mesh3d, &MeshMaterial3d<StandardMaterial>), With<Projectile>>()
+        .iter(world)
+        .map(|(mesh, material)| (mesh.id(), material.id()))
+        .collect();
+    assets.dedup();
+    assert_eq!(assets.len(), 1);
+}
+
+#[test]
+fn projectiles_deal_their_weapons_damage() {
+    let mut app = fire_once(WeaponDef {
+        damage: 42.0,
+        ..default()
+    });
+    let world = app.world_mut();
+    let damage: Vec<f32> = world
+        .query::<&Projectile>()
+        .iter(world)
+        .map(|projectile| projectile.damage)
+        .collect();
+    assert_eq!(damage, [42.0]);
+}
+
+#[test]
+fn hitscan_shots_leave_a_fading_tracer() {
+    let mut app = fire_once(WeaponDef {
+        mode: FireMode::Hitscan,
+        fire_rate: 0.1,
+        lifetime: 0.2,
+        ..default()
+    });
+    assert_eq!(count::<Projectile>(&mut app), 0);
+    assert_eq!(count::<Tracer>(&mut app), 1);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(count::<Tracer>(&mut app), 0);
+}
@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use game_demo::vehicle_systems::*;
+
+/// A chassis at the origin with wheels at (±1, -0.5, ±1.5) and the given
+/// compressions, front-left first.
+fn world_with_compression(compression: [f32; 4]) -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(SuspensionTuning::default());
+    let mut time = Time::<()>::default();
+    time.advance_by(Duration::from_secs_f32(1.0 / 60.0));
+    world.insert_resource(time);
+    let chassis = spawn_chassis(&mut world, compression);
+    (world, chassis)
+}
+
+fn spawn_chassis(world: &mut World, compression: [f32; 4]) -> Entity {
+    let chassis = world
+        .spawn((
+            Transform::default(),
+            GlobalTransform::default(),
+            ExternalForce::new(Vec3::ZERO).with_persistence(false),
+            Chassis { mass: 800.0 },
+        ))
+        .id();
+    let mounts = [
+        Vec3::new(-1.0, -0.5, 1.5),
+        Vec3::new(1.0, -0.5, 1.5),
+        Vec3::new(-1.0, -0.5, -1.5),
+        Vec3::new(1.0, -0.5, -1.5),
+    ];
+    for (mount, compression) in mounts.into_iter().zip(compression) {
+        let mut wheel = RaycastWheel::new(mount, 0.5, mount.z > 0.0, mount.x < 0.0, false);
+        wheel.grounded = true;
+        wheel.compression = compression;
+        wheel.prev_compression = compression;
+        wheel.contact_point = mount - Vec3::Y * 0.5;
+        let wheel = world.spawn(wheel).id();
+        world.entity_mut(chassis).add_child(wheel);
+    }
+    chassis
+}
+
+fn external_force(world: &World, chassis: Entity) -> ExternalForce {
+    *world.get::<ExternalForce>(chassis).unwrap()
+}
+
+#[test]
+fn even_compression_lifts_without_torque() {
+    let (mut world, chassis) = world_with_compression([0.1; 4]);
+    world.run_system_once(apply_suspension).unwrap();
+    let force = external_force(&world, chassis);
+    let tuning = SuspensionTuning::default();
+    assert!((force.force().y - 4.0 * tuning.k * 0.1).abs() < 1.0);
+    assert!(force.torque().length() < 1e-3);
+}
+
+#[test]
+fn spring_force_is_kept_apart_from_anti_roll() {
+    let (mut world, _) = world_with_compression([0.15, 0.05, 0.15, 0.05]);
+    world.run_system_once(apply_suspension).unwrap();
+    world.run_system_once(apply_anti_roll).unwrap();
+    let k = SuspensionTuning::default().k;
+    for wheel in world.query::<&RaycastWheel>().iter(&world) {
+        assert!((wheel.suspension_force - k * wheel.compression).abs() < 1.0);
+        assert_ne!(wheel.load, wheel.suspension_force);
+    }
+}
+
+#[test]
+fn contact_axes_follow_the_steered_wheel() {
+    let mut wheel = RaycastWheel::new(Vec3::ZERO, 0.5, true, true, false);
+    let (forward, side) = contact_axes(Quat::IDENTITY, &wheel);
+    assert!(forward.abs_diff_eq(Vec3::Z, 1e-6));
+    assert!(side.abs_diff_eq(Vec3::X, 1e-6));
+
+    // Steering left turns the heading towards +X.
+    wheel.steer_angle = std::f32::consts::FRAC_PI_2;
+    let (forward, _) = contact_axes(Quat::IDENTITY, &wheel);
+    assert!(forward.abs_diff_eq(Vec3::X, 1e-6));
+
+    // Axes stay in the ground plane on a slope.
+    wheel.steer_angle = 0.0;
+    wheel.contact_normal = Vec3::new(0.0, 1.0, -1.0).normalize();
+    let (forward, side) = contact_axes(Quat::IDENTITY, &wheel);
+    assert!(forward.dot(wheel.contact_normal).abs() < 1e-6);
+    assert!(side.dot(wheel.contact_normal).abs() < 1e-6);
+}
+
+#[test]
+fn compressed_front_pitches_the_nose_up() {
+    let (mut world, chassis) = world_with_compression([0.15, 0.15, 0.05, 0.05]);
+    world.run_system_once(apply_suspension).unwrap();
+    let torque = external_force(&world, chassis).torque();
+    // Positive rotation about X lowers +Z, so the front is pushed up by a
+    // negative torque.
+    assert!(torque.x < 0.0, "{torque}");
+    assert!(torque.z.abs() < 1e-3);
+}
+
+#[test]
+fn wheels_only_push_their_own_chassis() {
+    let (mut world, loaded) = world_with_compression([0.1; 4]);
+    let other = spawn_chassis(&mut world, [0.0; 4]);
+    let tuning = SuspensionTuning {
+        k: 2.0 * SuspensionTuning::default().k,
+        ..default()
+    };
+    world.entity_mut(other).insert(tuning);
+
+    world.run_system_once(apply_suspension).unwrap();
+    world.run_system_once(apply_anti_roll).unwrap();
+    assert!(external_force(&world, loaded).force().y > 0.0);
+    assert_eq!(external_force(&world, other).force(), Vec3::ZERO);
+
+    // A chassis' own tuning overrides the global one.
+    let (mut world, _) = world_with_compression([0.0; 4]);
+    let stiff = spawn_chassis(&mut world, [0.1; 4]);
+    world.entity_mut(stiff).insert(tuning);
+    world.run_system_once(apply_suspension).unwrap();
+    assert!((external_force(&world, stiff).force().y - 4.0 * tuning.k * 0.1).abs() < 1.0);
+}
+
+#[test]
+fn anti_roll_bar_is_a_force_pair() {
+    let (mut world, chassis) = world_with_compression([0.15, 0.05, 0.15, 0.05]);
+    world.run_system_once(apply_anti_roll).unwrap();
+    let force = external_force(&world, chassis);
+    assert!(force.force().length() < 1e-3);
+    // Negative torque about Z raises the more compressed left side.
+    assert!(force.torque().z < 0.0, "{}", force.torque());
+
+    let loads: Vec<f32> = world
+        .query::<&RaycastWheel>()
+        .iter(&world)
+        .map(|w| if w.is_left { w.load } else { -w.load })
+        .collect();
+    assert!(loads.iter().all(|&l| l >= 0.0), "{loads:?}");
+}